@@ -0,0 +1,86 @@
+//! Compares the monotonic stack selection against the original windowed search on long banks.
+//!
+//! Run with `cargo bench --bench day3`.
+
+use std::{hint::black_box, time::Instant};
+
+use advent_of_code_2025::batteries::select_max;
+
+/// Original approach: find the lowest index of the largest digit in a window, once per battery.
+fn select_max_windowed(digits: &[u8], k: usize) -> Vec<usize> {
+    let n = digits.len();
+    let mut selected = Vec::with_capacity(k);
+    let mut previous_idx: Option<usize> = None;
+
+    for i in (0..k).rev() {
+        let start_idx = previous_idx.map(|idx| idx + 1).unwrap_or(0);
+        let end_idx = n - i;
+
+        let (idx, _) = digits
+            .iter()
+            .enumerate()
+            .take(end_idx)
+            .skip(start_idx)
+            .min_by(|(idx1, digit1), (idx2, digit2)| {
+                digit1.cmp(digit2).reverse().then(idx1.cmp(idx2))
+            })
+            .unwrap();
+
+        previous_idx = Some(idx);
+        selected.push(idx);
+    }
+
+    selected
+}
+
+/// Generates a bank of digits 1-9 using a xorshift generator, so that runs are repeatable.
+fn generate_bank(len: usize, seed: u64) -> Vec<u8> {
+    let mut state = seed;
+
+    (0..len)
+        .map(|_| {
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            (state % 9) as u8 + 1
+        })
+        .collect()
+}
+
+fn time_ms(iterations: u32, mut f: impl FnMut() -> Vec<usize>) -> (f64, Vec<usize>) {
+    let start = Instant::now();
+    let mut result = Vec::new();
+
+    for _ in 0..iterations {
+        result = black_box(f());
+    }
+
+    (
+        start.elapsed().as_secs_f64() * 1000.0 / iterations as f64,
+        result,
+    )
+}
+
+fn main() {
+    println!(
+        "{:>10} {:>8} {:>14} {:>14}",
+        "bank len", "k", "windowed (ms)", "stack (ms)"
+    );
+
+    for bank_len in [100, 10_000, 100_000] {
+        let bank = generate_bank(bank_len, 0x2025_0003 + bank_len as u64);
+
+        for k in [12, bank_len / 10, bank_len / 2] {
+            let iterations = (1_000_000 / bank_len).max(1) as u32;
+
+            let (windowed_ms, windowed) = time_ms(iterations, || select_max_windowed(&bank, k));
+            let (stack_ms, stack) = time_ms(iterations, || select_max(&bank, k).unwrap());
+
+            assert_eq!(windowed, stack, "Approaches disagree for k = {}", k);
+            println!(
+                "{:>10} {:>8} {:>14.3} {:>14.3}",
+                bank_len, k, windowed_ms, stack_ms
+            );
+        }
+    }
+}
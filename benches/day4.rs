@@ -0,0 +1,152 @@
+//! Compares the worklist-based roll removal against the original full-grid rescans on large maps,
+//! and the bit-packed grid on maps that are too large for the other approaches.
+//!
+//! Run with `cargo bench --bench day4`.
+
+use std::{hint::black_box, time::Instant};
+
+use advent_of_code_2025::{
+    bit_grid::BitGrid,
+    grid::Grid,
+    paper_rolls::{ACCESSIBLE_THRESHOLD, CellType, Rules, removal_rounds},
+};
+
+/// Original approach: rescan the whole grid for accessible rolls at the start of every round.
+fn removal_rounds_rescan(grid: &Grid<CellType>) -> Vec<Vec<(usize, usize)>> {
+    let mut grid = grid.clone();
+    let mut counts = Grid::new(grid.rows(), grid.cols(), 0_u32);
+
+    for (row_idx, col_idx) in grid.positions() {
+        counts[(row_idx, col_idx)] = grid
+            .neighbours8(row_idx, col_idx)
+            .filter(|&position| grid[position] == CellType::PaperRoll)
+            .count() as u32;
+    }
+
+    let mut rounds = Vec::new();
+
+    loop {
+        let accessible_rolls = grid
+            .iter()
+            .filter(|&(position, cell)| {
+                *cell == CellType::PaperRoll && counts[position] < ACCESSIBLE_THRESHOLD
+            })
+            .map(|(position, _)| position)
+            .collect::<Vec<_>>();
+
+        if accessible_rolls.is_empty() {
+            break;
+        }
+
+        for &(row_idx, col_idx) in accessible_rolls.iter() {
+            for position in grid.neighbours8(row_idx, col_idx) {
+                counts[position] -= 1;
+            }
+
+            grid[(row_idx, col_idx)] = CellType::Empty;
+        }
+
+        rounds.push(accessible_rolls);
+    }
+
+    rounds
+}
+
+/// Generates a square map using a xorshift generator, so that runs are repeatable.
+///
+/// Roughly 3 in 4 cells have rolls, which gives a similar number of rounds to the puzzle input.
+fn generate_grid(size: usize, seed: u64) -> Grid<CellType> {
+    let mut state = seed;
+    let mut grid = Grid::new(size, size, CellType::Empty);
+
+    for position in grid.positions() {
+        if has_roll(&mut state) {
+            grid[position] = CellType::PaperRoll;
+        }
+    }
+
+    grid
+}
+
+/// Generates the same map as `generate_grid`, but straight into a bit-packed grid.
+fn generate_bit_grid(size: usize, seed: u64) -> BitGrid {
+    let mut state = seed;
+    let mut grid = BitGrid::new(size, size);
+
+    for row_idx in 0..size {
+        for col_idx in 0..size {
+            if has_roll(&mut state) {
+                grid.set(row_idx, col_idx, true);
+            }
+        }
+    }
+
+    grid
+}
+
+fn has_roll(state: &mut u64) -> bool {
+    *state ^= *state << 13;
+    *state ^= *state >> 7;
+    *state ^= *state << 17;
+
+    !state.is_multiple_of(4)
+}
+
+fn time_ms<T>(f: impl FnOnce() -> T) -> (f64, T) {
+    let start = Instant::now();
+    let result = black_box(f());
+
+    (start.elapsed().as_secs_f64() * 1000.0, result)
+}
+
+fn main() {
+    println!(
+        "{:>10} {:>8} {:>12} {:>14} {:>12}",
+        "size", "rounds", "rescan (ms)", "worklist (ms)", "bitset (ms)"
+    );
+
+    for size in [100, 500, 1_000, 2_000] {
+        let grid = generate_grid(size, 0x2025_0004 + size as u64);
+        let bit_grid = BitGrid::from_grid(&grid, |cell| *cell == CellType::PaperRoll);
+
+        let (rescan_ms, mut rescan) = time_ms(|| removal_rounds_rescan(&grid));
+        let (worklist_ms, mut worklist) = time_ms(|| removal_rounds(&grid, &Rules::default()));
+        let (bitset_ms, bitset) = time_ms(|| bit_grid.peel(ACCESSIBLE_THRESHOLD));
+
+        // Rolls within a round can be found in a different order, but the rounds must match
+        for round in rescan.iter_mut().chain(worklist.iter_mut()) {
+            round.sort_unstable();
+        }
+
+        assert_eq!(rescan, worklist, "Approaches disagree for size {}", size);
+        assert_eq!(
+            worklist.iter().map(|round| round.len()).collect::<Vec<_>>(),
+            bitset,
+            "Bitset disagrees for size {}",
+            size
+        );
+        println!(
+            "{:>10} {:>8} {:>12.1} {:>14.1} {:>12.1}",
+            size,
+            worklist.len(),
+            rescan_ms,
+            worklist_ms,
+            bitset_ms
+        );
+    }
+
+    // Only the bitset is run here, as the other approaches need several GB for this many cells
+    for size in [5_000, 10_000] {
+        let bit_grid = generate_bit_grid(size, 0x2025_0004 + size as u64);
+        let (bitset_ms, bitset) = time_ms(|| bit_grid.peel(ACCESSIBLE_THRESHOLD));
+
+        println!(
+            "{:>10} {:>8} {:>12} {:>14} {:>12.1}",
+            size,
+            bitset.len(),
+            "-",
+            "-",
+            bitset_ms
+        );
+    }
+}
@@ -0,0 +1,651 @@
+//! Helpers for choosing which batteries to enable in a bank (day 3).
+
+use std::{error::Error, fmt};
+
+/// Reasons why batteries couldn't be selected from a bank.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum SelectionError {
+    /// The bank has fewer batteries than need to be enabled.
+    NotEnoughBatteries { available: usize, required: usize },
+
+    /// The constraints don't allow enough batteries to be enabled together.
+    ConstraintsTooStrict { selectable: usize, required: usize },
+}
+
+impl fmt::Display for SelectionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SelectionError::NotEnoughBatteries {
+                available,
+                required,
+            } => write!(
+                f,
+                "Bank has {} batteries, but {} need to be enabled",
+                available, required
+            ),
+            SelectionError::ConstraintsTooStrict {
+                selectable,
+                required,
+            } => write!(
+                f,
+                "Constraints only allow {} batteries to be enabled, but {} need to be",
+                selectable, required
+            ),
+        }
+    }
+}
+
+impl Error for SelectionError {}
+
+/// Picks the positions of `k` digits, in ascending order, that form the largest possible number.
+/// Both parts of day 3 use this, through [`select`] with the default objective.
+///
+/// This uses a monotonic stack, so it runs in `O(n)` time rather than searching a window for
+/// each selected digit. Each digit is pushed once, and is popped when a larger digit comes along
+/// while there are still digits that can be dropped.
+pub fn select_max(digits: &[u8], k: usize) -> Result<Vec<usize>, SelectionError> {
+    if k > digits.len() {
+        return Err(SelectionError::NotEnoughBatteries {
+            available: digits.len(),
+            required: k,
+        });
+    }
+
+    let mut remaining_drops = digits.len() - k;
+    let mut stack: Vec<usize> = Vec::with_capacity(digits.len());
+
+    for (idx, &digit) in digits.iter().enumerate() {
+        // Dropping a smaller digit in favour of a larger one always gives a larger number
+        // NOTE: Equal digits are kept, so the earliest battery is chosen on ties
+        while remaining_drops > 0 && stack.last().is_some_and(|&top| digits[top] < digit) {
+            stack.pop();
+            remaining_drops -= 1;
+        }
+
+        stack.push(idx);
+    }
+
+    // Any leftover drops come off the end, as those are the smallest trailing digits
+    stack.truncate(k);
+    Ok(stack)
+}
+
+/// What the number formed by the enabled batteries should be.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub enum Objective {
+    /// Largest possible number.
+    #[default]
+    Max,
+
+    /// Smallest possible number.
+    Min,
+
+    /// Number that equals the target, or is as close to it as possible (preferring the lower one
+    /// on ties). The target is given as its digits, most significant first.
+    Closest(Vec<u8>),
+}
+
+/// Restrictions on which batteries can be enabled together.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Constraints {
+    /// No two adjacent batteries can be enabled.
+    pub no_adjacent: bool,
+
+    /// At most one battery can be enabled from each group of this many batteries, i.e. positions
+    /// `0..m`, `m..2m` and so on.
+    pub group_size: Option<usize>,
+}
+
+impl Constraints {
+    fn is_unconstrained(&self) -> bool {
+        !self.no_adjacent && self.group_size.is_none()
+    }
+
+    /// Gets the first position that can be enabled after enabling `idx`.
+    ///
+    /// Every constraint is of this form, which is what makes the selection work: enabling an
+    /// earlier battery never leaves fewer options for the remaining batteries.
+    fn next_allowed(&self, idx: usize) -> usize {
+        let mut next_idx = idx + 1;
+
+        if self.no_adjacent {
+            next_idx = next_idx.max(idx + 2);
+        }
+
+        if let Some(group_size) = self.group_size {
+            next_idx = next_idx.max(((idx / group_size) + 1) * group_size);
+        }
+
+        next_idx
+    }
+}
+
+/// Picks the positions of `k` digits, in ascending order, that best meet the objective while
+/// following the constraints.
+///
+/// The plain maximum uses the monotonic stack from [`select_max`]. Everything else picks digits
+/// greedily from a window, where the window is worked out using a table of how many batteries can
+/// still be enabled from each position onwards.
+pub fn select(
+    digits: &[u8],
+    k: usize,
+    objective: &Objective,
+    constraints: Constraints,
+) -> Result<Vec<usize>, SelectionError> {
+    if k > digits.len() {
+        return Err(SelectionError::NotEnoughBatteries {
+            available: digits.len(),
+            required: k,
+        });
+    }
+
+    if *objective == Objective::Max && constraints.is_unconstrained() {
+        return select_max(digits, k);
+    }
+
+    let selector = Selector::new(digits, constraints);
+    if selector.selectable[0] < k {
+        return Err(SelectionError::ConstraintsTooStrict {
+            selectable: selector.selectable[0],
+            required: k,
+        });
+    }
+
+    let positions = match objective {
+        Objective::Max => selector.fill(0, k, true),
+        Objective::Min => selector.fill(0, k, false),
+        Objective::Closest(target) => selector.closest(k, target),
+    };
+
+    Ok(positions)
+}
+
+/// Selects digits under constraints, using a dynamic programming table for feasibility.
+///
+/// `selectable[i]` is the most batteries that can be enabled from positions `i..`, which is either
+/// the same as for `i + 1` (skip `i`), or 1 more than for the next allowed position (enable `i`).
+struct Selector<'a> {
+    digits: &'a [u8],
+    constraints: Constraints,
+    selectable: Vec<usize>,
+}
+
+impl<'a> Selector<'a> {
+    fn new(digits: &'a [u8], constraints: Constraints) -> Self {
+        let n = digits.len();
+        let mut selectable = vec![0; n + 1];
+
+        for idx in (0..n).rev() {
+            let next_idx = constraints.next_allowed(idx).min(n);
+            selectable[idx] = selectable[idx + 1].max(1 + selectable[next_idx]);
+        }
+
+        Selector {
+            digits,
+            constraints,
+            selectable,
+        }
+    }
+
+    fn next_allowed(&self, idx: usize) -> usize {
+        self.constraints.next_allowed(idx).min(self.digits.len())
+    }
+
+    /// Gets the positions that can be enabled next, starting from `start`, so that there's still
+    /// room for the rest of the `remaining` batteries afterwards.
+    ///
+    /// `selectable` never increases, so these positions are always a contiguous block.
+    fn window(&self, start: usize, remaining: usize) -> impl Iterator<Item = usize> + '_ {
+        (start..self.digits.len())
+            .take_while(move |&idx| self.selectable[self.next_allowed(idx)] + 1 >= remaining)
+    }
+
+    /// Picks the earliest position in the window with the best digit that matches the filter.
+    ///
+    /// The earliest position is always at least as good, as it leaves the most room afterwards.
+    fn pick(
+        &self,
+        start: usize,
+        remaining: usize,
+        prefer_larger: bool,
+        filter: impl Fn(u8) -> bool,
+    ) -> Option<usize> {
+        self.window(start, remaining)
+            .filter(|&idx| filter(self.digits[idx]))
+            .reduce(|best_idx, idx| {
+                let (best, digit) = (self.digits[best_idx], self.digits[idx]);
+                let is_better = if prefer_larger {
+                    digit > best
+                } else {
+                    digit < best
+                };
+
+                if is_better { idx } else { best_idx }
+            })
+    }
+
+    /// Greedily enables `remaining` batteries from `start` onwards, making the largest (or
+    /// smallest) number. Assumes that there's room for all of them.
+    fn fill(&self, start: usize, remaining: usize, prefer_larger: bool) -> Vec<usize> {
+        let mut positions = Vec::with_capacity(remaining);
+        let mut start = start;
+
+        for remaining in (1..=remaining).rev() {
+            let idx = self
+                .pick(start, remaining, prefer_larger, |_| true)
+                .unwrap();
+            positions.push(idx);
+            start = self.next_allowed(idx);
+        }
+
+        positions
+    }
+
+    /// Finds the number closest to the target, by comparing the closest numbers on either side.
+    fn closest(&self, k: usize, target: &[u8]) -> Vec<usize> {
+        // Strip leading zeros, then pad the target out to k digits
+        let significant = target.iter().skip_while(|&&digit| digit == 0).count();
+        if significant > k {
+            // Target is larger than any k-digit number
+            return self.fill(0, k, true);
+        }
+
+        let mut padded_target = vec![0; k - significant];
+        padded_target.extend_from_slice(&target[(target.len() - significant)..]);
+
+        let below = self.bound(k, &padded_target, true);
+        let above = self.bound(k, &padded_target, false);
+
+        match (below, above) {
+            (Some(below), Some(above)) => {
+                let below_gap = digit_difference(&padded_target, &self.to_digits(&below));
+                let above_gap = digit_difference(&self.to_digits(&above), &padded_target);
+
+                if below_gap <= above_gap { below } else { above }
+            }
+            (Some(below), None) => below,
+            (None, Some(above)) => above,
+            (None, None) => unreachable!("There's always a number on at least one side"),
+        }
+    }
+
+    /// Finds the largest number `<= target` (if `below`), or the smallest number `>= target`.
+    ///
+    /// The answer matches some prefix of the target, then has a smaller (or larger) digit, then
+    /// is filled in greedily. A longer matching prefix is always closer, so this follows the target
+    /// for as long as possible and keeps the last place where it could branch off.
+    fn bound(&self, k: usize, target: &[u8], below: bool) -> Option<Vec<usize>> {
+        let mut prefix: Vec<usize> = Vec::with_capacity(k);
+        let mut best: Option<Vec<usize>> = None;
+        let mut start = 0;
+
+        for (i, &target_digit) in target.iter().enumerate() {
+            let remaining = k - i;
+
+            // Branch off here with the closest digit on the right side of the target digit
+            let branch_idx = self.pick(start, remaining, below, |digit| {
+                if below {
+                    digit < target_digit
+                } else {
+                    digit > target_digit
+                }
+            });
+
+            if let Some(branch_idx) = branch_idx {
+                let mut positions = prefix.clone();
+                positions.push(branch_idx);
+                positions.extend(self.fill(self.next_allowed(branch_idx), remaining - 1, below));
+
+                best = Some(positions);
+            }
+
+            // Then try to keep following the target
+            let match_idx = self.pick(start, remaining, below, |digit| digit == target_digit);
+
+            match match_idx {
+                Some(match_idx) => {
+                    prefix.push(match_idx);
+                    start = self.next_allowed(match_idx);
+                }
+                None => return best,
+            }
+        }
+
+        // Matched the whole target
+        Some(prefix)
+    }
+
+    fn to_digits(&self, positions: &[usize]) -> Vec<u8> {
+        positions.iter().map(|&idx| self.digits[idx]).collect()
+    }
+}
+
+/// Subtracts two numbers with the same number of digits, where `a >= b`.
+fn digit_difference(a: &[u8], b: &[u8]) -> Vec<u8> {
+    let mut difference = vec![0; a.len()];
+    let mut borrow = 0;
+
+    for idx in (0..a.len()).rev() {
+        let mut digit = a[idx] as i8 - b[idx] as i8 - borrow;
+        borrow = 0;
+
+        if digit < 0 {
+            digit += 10;
+            borrow = 1;
+        }
+
+        difference[idx] = digit as u8;
+    }
+
+    difference
+}
+
+/// Joltage formed by the enabled batteries, which only stores values that fit in 64 bits.
+///
+/// Arithmetic is checked, so this returns `None` instead of silently overflowing. Enable the
+/// `big-joltage` feature to store any value exactly.
+#[cfg(not(feature = "big-joltage"))]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Joltage(u64);
+
+#[cfg(not(feature = "big-joltage"))]
+impl Joltage {
+    /// Converts a list of digits (most significant first) into a joltage.
+    pub fn from_digits(digits: impl IntoIterator<Item = u8>) -> Option<Self> {
+        digits
+            .into_iter()
+            .try_fold(0_u64, |acc, digit| {
+                acc.checked_mul(10)?.checked_add(digit as u64)
+            })
+            .map(Joltage)
+    }
+
+    pub fn checked_add(&self, other: &Self) -> Option<Self> {
+        self.0.checked_add(other.0).map(Joltage)
+    }
+}
+
+#[cfg(not(feature = "big-joltage"))]
+impl fmt::Display for Joltage {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+/// Joltage formed by the enabled batteries, which is stored exactly regardless of its size.
+///
+/// Digits are stored least significant first, without any leading zeros, so `0` has no digits.
+/// Arithmetic never fails, but keeps the same API as the 64-bit version.
+#[cfg(feature = "big-joltage")]
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Joltage {
+    digits: Vec<u8>,
+}
+
+#[cfg(feature = "big-joltage")]
+impl Joltage {
+    /// Converts a list of digits (most significant first) into a joltage.
+    pub fn from_digits(digits: impl IntoIterator<Item = u8>) -> Option<Self> {
+        let mut digits = digits.into_iter().collect::<Vec<_>>();
+        digits.reverse();
+
+        while digits.last() == Some(&0) {
+            digits.pop();
+        }
+
+        Some(Joltage { digits })
+    }
+
+    pub fn checked_add(&self, other: &Self) -> Option<Self> {
+        let len = self.digits.len().max(other.digits.len());
+        let mut digits = Vec::with_capacity(len + 1);
+        let mut carry = 0;
+
+        for idx in 0..len {
+            let sum =
+                self.digits.get(idx).unwrap_or(&0) + other.digits.get(idx).unwrap_or(&0) + carry;
+            digits.push(sum % 10);
+            carry = sum / 10;
+        }
+
+        if carry > 0 {
+            digits.push(carry);
+        }
+
+        Some(Joltage { digits })
+    }
+}
+
+#[cfg(feature = "big-joltage")]
+impl fmt::Display for Joltage {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.digits.is_empty() {
+            return write!(f, "0");
+        }
+
+        let digits_str = self
+            .digits
+            .iter()
+            .rev()
+            .map(|digit| char::from(b'0' + digit))
+            .collect::<String>();
+
+        write!(f, "{}", digits_str)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CONSTRAINTS: [Constraints; 5] = [
+        Constraints {
+            no_adjacent: false,
+            group_size: None,
+        },
+        Constraints {
+            no_adjacent: true,
+            group_size: None,
+        },
+        Constraints {
+            no_adjacent: false,
+            group_size: Some(2),
+        },
+        Constraints {
+            no_adjacent: false,
+            group_size: Some(3),
+        },
+        Constraints {
+            no_adjacent: true,
+            group_size: Some(3),
+        },
+    ];
+
+    /// Generates a bank of digits 0-9 using a xorshift generator, so that failures can be
+    /// reproduced.
+    fn generate_digits(len: usize, state: &mut u64) -> Vec<u8> {
+        (0..len)
+            .map(|_| {
+                *state ^= *state << 13;
+                *state ^= *state >> 7;
+                *state ^= *state << 17;
+                (*state % 10) as u8
+            })
+            .collect()
+    }
+
+    fn to_number(digits: &[u8], positions: &[usize]) -> u64 {
+        positions
+            .iter()
+            .fold(0, |acc, &idx| (acc * 10) + digits[idx] as u64)
+    }
+
+    /// Lists every allowed set of positions, by checking every subset of the bank.
+    fn allowed_selections(digits: &[u8], constraints: Constraints) -> Vec<Vec<usize>> {
+        (0_u32..(1 << digits.len()))
+            .map(|mask| {
+                (0..digits.len())
+                    .filter(|idx| mask & (1 << idx) != 0)
+                    .collect::<Vec<_>>()
+            })
+            .filter(|positions| is_allowed(positions, constraints))
+            .collect()
+    }
+
+    fn is_allowed(positions: &[usize], constraints: Constraints) -> bool {
+        positions
+            .windows(2)
+            .all(|pair| pair[1] >= constraints.next_allowed(pair[0]))
+    }
+
+    /// Finds the best number out of every allowed selection, using `key` to rank them (smallest
+    /// first).
+    fn best_number<K: Ord>(
+        digits: &[u8],
+        selections: &[Vec<usize>],
+        k: usize,
+        key: impl Fn(u64) -> K,
+    ) -> Option<u64> {
+        selections
+            .iter()
+            .filter(|positions| positions.len() == k)
+            .map(|positions| to_number(digits, positions))
+            .min_by_key(|number| key(*number))
+    }
+
+    #[test]
+    fn matches_every_selection_on_small_banks() {
+        let mut state = 0x2025_0003;
+
+        for len in 1..=9 {
+            for _ in 0..20 {
+                let digits = generate_digits(len, &mut state);
+
+                for constraints in CONSTRAINTS {
+                    let selections = allowed_selections(&digits, constraints);
+                    let most_selectable = selections.iter().map(|positions| positions.len()).max();
+
+                    for k in 1..=len {
+                        let mut objectives = vec![
+                            (
+                                Objective::Max,
+                                best_number(&digits, &selections, k, |n| u64::MAX - n),
+                            ),
+                            (Objective::Min, best_number(&digits, &selections, k, |n| n)),
+                        ];
+
+                        // Targets with fewer, the same and more digits, including leading zeros
+                        for target_len in [k - 1, k, k + 1] {
+                            let target = generate_digits(target_len, &mut state);
+                            let target_number =
+                                to_number(&target, &(0..target_len).collect::<Vec<_>>());
+
+                            // Ties go to the lower number
+                            let closest = best_number(&digits, &selections, k, |n| {
+                                (n.abs_diff(target_number), n)
+                            });
+                            objectives.push((Objective::Closest(target), closest));
+                        }
+
+                        for (objective, expected) in objectives {
+                            let result = select(&digits, k, &objective, constraints);
+                            let context = format!(
+                                "{:?}, k = {}, {:?}, {:?}",
+                                digits, k, objective, constraints
+                            );
+
+                            match expected {
+                                Some(expected) => {
+                                    let positions = result.expect(&context);
+
+                                    assert_eq!(positions.len(), k, "{}", context);
+                                    assert!(positions.is_sorted(), "{}", context);
+                                    assert!(is_allowed(&positions, constraints), "{}", context);
+                                    assert_eq!(
+                                        to_number(&digits, &positions),
+                                        expected,
+                                        "{}",
+                                        context
+                                    );
+                                }
+                                None => assert_eq!(
+                                    result,
+                                    Err(SelectionError::ConstraintsTooStrict {
+                                        selectable: most_selectable.unwrap(),
+                                        required: k,
+                                    }),
+                                    "{}",
+                                    context
+                                ),
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn matches_the_windowed_search_without_constraints() {
+        let mut state = 0x2025_0031;
+
+        for len in 1..=30 {
+            let digits = generate_digits(len, &mut state);
+
+            for k in 1..=len {
+                // Original approach: take the first largest digit that leaves room for the rest
+                let mut expected = Vec::with_capacity(k);
+                let mut start = 0;
+
+                for remaining in (1..=k).rev() {
+                    let window = start..=(len - remaining);
+                    let best = window.clone().map(|idx| digits[idx]).max().unwrap();
+                    let idx = window.into_iter().find(|&idx| digits[idx] == best).unwrap();
+
+                    expected.push(idx);
+                    start = idx + 1;
+                }
+
+                assert_eq!(
+                    select_max(&digits, k),
+                    Ok(expected),
+                    "{:?}, k = {}",
+                    digits,
+                    k
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn rejects_too_few_batteries() {
+        for objective in [Objective::Max, Objective::Min, Objective::Closest(vec![5])] {
+            for constraints in CONSTRAINTS {
+                assert_eq!(
+                    select(&[1, 2, 3], 4, &objective, constraints),
+                    Err(SelectionError::NotEnoughBatteries {
+                        available: 3,
+                        required: 4,
+                    })
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn prefers_the_lower_number_on_ties() {
+        // 15 and 25 are both 5 away from 20
+        let constraints = Constraints::default();
+        let positions = select(
+            &[1, 5, 2, 5],
+            2,
+            &Objective::Closest(vec![2, 0]),
+            constraints,
+        );
+
+        assert_eq!(
+            positions.map(|positions| to_number(&[1, 5, 2, 5], &positions)),
+            Ok(15)
+        );
+    }
+}
@@ -0,0 +1,427 @@
+//! Bit-packed 2D grid of booleans, for binary maps that are too large to store a struct per cell
+//! (e.g. day 4 paper rolls).
+//!
+//! Each row is stored as 64-bit words, where bit `i` of word `w` is the cell in column
+//! `(w * 64) + i`. Neighbour counts are calculated for a whole word at once, by shifting the
+//! neighbouring words into place and adding them up with bitwise adders.
+
+use crate::grid::Grid;
+
+const WORD_BITS: usize = u64::BITS as usize;
+
+/// Grid of booleans, stored as one bit per cell.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct BitGrid {
+    rows: usize,
+    cols: usize,
+    words_per_row: usize,
+
+    /// NOTE: Bits past the last column are always clear, so they never count as neighbours.
+    words: Vec<u64>,
+}
+
+impl BitGrid {
+    /// Creates a grid where every cell is clear.
+    pub fn new(rows: usize, cols: usize) -> Self {
+        let words_per_row = cols.div_ceil(WORD_BITS);
+
+        BitGrid {
+            rows,
+            cols,
+            words_per_row,
+            words: vec![0; rows * words_per_row],
+        }
+    }
+
+    /// Creates a grid from another grid, setting the cells that match `is_set`.
+    pub fn from_grid<T>(grid: &Grid<T>, mut is_set: impl FnMut(&T) -> bool) -> Self {
+        let mut bit_grid = BitGrid::new(grid.rows(), grid.cols());
+
+        for ((row_idx, col_idx), cell) in grid.iter() {
+            if is_set(cell) {
+                bit_grid.set(row_idx, col_idx, true);
+            }
+        }
+
+        bit_grid
+    }
+
+    pub const fn rows(&self) -> usize {
+        self.rows
+    }
+
+    pub const fn cols(&self) -> usize {
+        self.cols
+    }
+
+    const fn word_position(&self, row: usize, col: usize) -> (usize, u64) {
+        let idx = (row * self.words_per_row) + (col / WORD_BITS);
+        (idx, 1 << (col % WORD_BITS))
+    }
+
+    pub fn get(&self, row: usize, col: usize) -> Option<bool> {
+        if row < self.rows && col < self.cols {
+            let (idx, bit) = self.word_position(row, col);
+            Some(self.words[idx] & bit != 0)
+        } else {
+            None
+        }
+    }
+
+    /// Sets or clears a cell, panicking if it's outside of the grid.
+    pub fn set(&mut self, row: usize, col: usize, value: bool) {
+        assert!(
+            row < self.rows && col < self.cols,
+            "Position ({}, {}) is outside of a {}x{} grid",
+            row,
+            col,
+            self.rows,
+            self.cols
+        );
+
+        let (idx, bit) = self.word_position(row, col);
+        if value {
+            self.words[idx] |= bit;
+        } else {
+            self.words[idx] &= !bit;
+        }
+    }
+
+    /// Counts the cells that are set.
+    pub fn count_ones(&self) -> usize {
+        self.words
+            .iter()
+            .map(|word| word.count_ones() as usize)
+            .sum()
+    }
+
+    /// Iterates over the positions of the cells that are set, in row-major order.
+    pub fn ones(&self) -> impl Iterator<Item = (usize, usize)> + '_ {
+        self.words.iter().enumerate().flat_map(move |(idx, word)| {
+            let row = idx / self.words_per_row;
+            let first_col = (idx % self.words_per_row) * WORD_BITS;
+
+            BitIter(*word).map(move |bit| (row, first_col + bit))
+        })
+    }
+
+    /// Finds the set cells that have fewer than `threshold` set cells in the 8 surrounding cells.
+    /// Cells outside the grid count as clear.
+    pub fn with_fewer_neighbours(&self, threshold: u32) -> BitGrid {
+        let mut result = BitGrid::new(self.rows, self.cols);
+
+        for idx in 0..self.words.len() {
+            result.words[idx] = self.words[idx] & self.fewer_neighbours_word(idx, threshold);
+        }
+
+        result
+    }
+
+    /// Repeatedly clears every set cell with fewer than `threshold` set neighbours (like
+    /// [`Self::with_fewer_neighbours`]), until there aren't any left to clear. Returns how many
+    /// cells were cleared in each round.
+    ///
+    /// Cells in the same round are cleared at the same time. After the first round, only the words
+    /// next to a cleared cell are checked again, since nothing else can have changed.
+    pub fn peel(&self, threshold: u32) -> Vec<usize> {
+        let mut grid = self.clone();
+        let mut round_counts = Vec::new();
+
+        let mut dirty_words = (0..grid.words.len()).collect::<Vec<_>>();
+        let mut is_dirty = vec![true; grid.words.len()];
+
+        loop {
+            // Find the whole round before clearing anything, so that it doesn't affect the counts
+            let mut cleared = Vec::new();
+
+            for idx in dirty_words.drain(..) {
+                is_dirty[idx] = false;
+
+                let mask = grid.words[idx] & grid.fewer_neighbours_word(idx, threshold);
+                if mask != 0 {
+                    cleared.push((idx, mask));
+                }
+            }
+
+            if cleared.is_empty() {
+                break;
+            }
+
+            round_counts.push(
+                cleared
+                    .iter()
+                    .map(|(_, mask)| mask.count_ones() as usize)
+                    .sum(),
+            );
+
+            for &(idx, mask) in cleared.iter() {
+                grid.words[idx] &= !mask;
+
+                let row = idx / grid.words_per_row;
+                let word_col = idx % grid.words_per_row;
+
+                // Words to the sides only change if a cell on the edge of this word was cleared
+                let first_word_col = if mask & 1 != 0 {
+                    word_col.saturating_sub(1)
+                } else {
+                    word_col
+                };
+                let last_word_col = if mask >> (WORD_BITS - 1) != 0 {
+                    (word_col + 1).min(grid.words_per_row - 1)
+                } else {
+                    word_col
+                };
+
+                for neighbour_row in row.saturating_sub(1)..=(row + 1).min(grid.rows - 1) {
+                    for neighbour_word_col in first_word_col..=last_word_col {
+                        let neighbour_idx =
+                            (neighbour_row * grid.words_per_row) + neighbour_word_col;
+
+                        if !is_dirty[neighbour_idx] {
+                            is_dirty[neighbour_idx] = true;
+                            dirty_words.push(neighbour_idx);
+                        }
+                    }
+                }
+            }
+        }
+
+        round_counts
+    }
+
+    /// Finds which cells in a word have fewer than `threshold` set neighbours (whether or not the
+    /// cells themselves are set).
+    fn fewer_neighbours_word(&self, idx: usize, threshold: u32) -> u64 {
+        let row = idx / self.words_per_row;
+        let word_col = idx % self.words_per_row;
+
+        let row_words =
+            |row: usize| &self.words[(row * self.words_per_row)..][..self.words_per_row];
+        let (above_west, above, above_east) = match row.checked_sub(1) {
+            Some(above_row) => shifted_words(row_words(above_row), word_col),
+            None => (0, 0, 0),
+        };
+        let (west, _, east) = shifted_words(row_words(row), word_col);
+        let (below_west, below, below_east) = if row + 1 < self.rows {
+            shifted_words(row_words(row + 1), word_col)
+        } else {
+            (0, 0, 0)
+        };
+
+        // Add up the 8 neighbours for every bit at once, giving a 4-bit count per bit
+        let (sum_1, carry_1) = full_add(above_west, above, above_east);
+        let (sum_2, carry_2) = full_add(west, east, below_west);
+        let (sum_3, carry_3) = half_add(below, below_east);
+
+        let (ones, carry_4) = full_add(sum_1, sum_2, sum_3);
+        let (twos_1, fours_1) = full_add(carry_1, carry_2, carry_3);
+        let (twos, fours_2) = half_add(twos_1, carry_4);
+        let (fours, eights) = half_add(fours_1, fours_2);
+
+        less_than([ones, twos, fours, eights], threshold)
+    }
+}
+
+/// Gets a word along with copies shifted so that each bit holds its west (`col - 1`) and east
+/// (`col + 1`) neighbour.
+fn shifted_words(row: &[u64], word_col: usize) -> (u64, u64, u64) {
+    let word = row[word_col];
+    let previous = word_col.checked_sub(1).map_or(0, |prev_col| row[prev_col]);
+    let next = row.get(word_col + 1).copied().unwrap_or(0);
+
+    let west = (word << 1) | (previous >> (WORD_BITS - 1));
+    let east = (word >> 1) | (next << (WORD_BITS - 1));
+
+    (west, word, east)
+}
+
+/// Adds 3 bits in each position, giving the sum and carry bits.
+const fn full_add(a: u64, b: u64, c: u64) -> (u64, u64) {
+    let partial = a ^ b;
+    (partial ^ c, (a & b) | (partial & c))
+}
+
+/// Adds 2 bits in each position, giving the sum and carry bits.
+const fn half_add(a: u64, b: u64) -> (u64, u64) {
+    (a ^ b, a & b)
+}
+
+/// Compares a 4-bit count in each position (least significant bit first) against `threshold`.
+fn less_than(count_bits: [u64; 4], threshold: u32) -> u64 {
+    // Counts are at most 8, so everything is less than 16 or more
+    if threshold >= 16 {
+        return u64::MAX;
+    }
+
+    // Compare from the most significant bit, tracking which counts are still equal so far
+    let mut less = 0;
+    let mut equal = u64::MAX;
+
+    for (bit_idx, count_bit) in count_bits.iter().enumerate().rev() {
+        if threshold & (1 << bit_idx) != 0 {
+            less |= equal & !count_bit;
+            equal &= count_bit;
+        } else {
+            equal &= !count_bit;
+        }
+    }
+
+    less
+}
+
+/// Iterates over the indices of the set bits in a word, from least significant.
+struct BitIter(u64);
+
+impl Iterator for BitIter {
+    type Item = usize;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.0 == 0 {
+            return None;
+        }
+
+        let bit = self.0.trailing_zeros() as usize;
+        self.0 &= self.0 - 1;
+        Some(bit)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::paper_rolls::{CellType, Rules, count_accessible, removal_rounds};
+
+    /// Widths on either side of the word boundaries, which is where the shifts carry between words.
+    const WIDTHS: [usize; 5] = [1, 63, 64, 65, 129];
+
+    const THRESHOLDS: [u32; 6] = [0, 1, 4, 8, 9, 16];
+
+    /// Generates a map where roughly `density` out of 8 cells have a roll, using a fixed seed so
+    /// that failures can be reproduced.
+    fn generate_grid(rows: usize, cols: usize, density: u64, seed: u64) -> Grid<CellType> {
+        let mut state = seed.wrapping_mul(0x9e37_79b9_7f4a_7c15) | 1;
+
+        let cells = (0..(rows * cols))
+            .map(|_| {
+                // xorshift64
+                state ^= state << 13;
+                state ^= state >> 7;
+                state ^= state << 17;
+
+                if state % 8 < density {
+                    CellType::PaperRoll
+                } else {
+                    CellType::Empty
+                }
+            })
+            .collect();
+
+        Grid::from_vec(rows, cols, cells).unwrap()
+    }
+
+    fn test_grids() -> impl Iterator<Item = Grid<CellType>> {
+        WIDTHS.into_iter().flat_map(|cols| {
+            [1, 2, 5].into_iter().flat_map(move |rows| {
+                [3, 6, 8].map(|density| generate_grid(rows, cols, density, (rows * cols) as u64))
+            })
+        })
+    }
+
+    fn rules(threshold: u32) -> Rules {
+        Rules {
+            threshold,
+            ..Rules::default()
+        }
+    }
+
+    #[test]
+    fn round_trips_cells() {
+        for grid in test_grids() {
+            let bit_grid = BitGrid::from_grid(&grid, |cell| *cell == CellType::PaperRoll);
+
+            for ((row, col), cell) in grid.iter() {
+                assert_eq!(bit_grid.get(row, col), Some(*cell == CellType::PaperRoll));
+            }
+
+            assert_eq!(bit_grid.get(grid.rows(), 0), None);
+            assert_eq!(bit_grid.get(0, grid.cols()), None);
+            assert_eq!(
+                bit_grid.ones().collect::<Vec<_>>(),
+                grid.iter()
+                    .filter(|(_, cell)| **cell == CellType::PaperRoll)
+                    .map(|(position, _)| position)
+                    .collect::<Vec<_>>()
+            );
+        }
+    }
+
+    #[test]
+    fn finds_the_same_accessible_rolls_as_the_grid() {
+        for grid in test_grids() {
+            let bit_grid = BitGrid::from_grid(&grid, |cell| *cell == CellType::PaperRoll);
+
+            for threshold in THRESHOLDS {
+                let rules = rules(threshold);
+                let accessible = bit_grid.with_fewer_neighbours(threshold);
+
+                let mut expected = removal_rounds(&grid, &rules)
+                    .into_iter()
+                    .next()
+                    .unwrap_or_default();
+                expected.sort_unstable();
+
+                assert_eq!(
+                    accessible.ones().collect::<Vec<_>>(),
+                    expected,
+                    "{}x{} grid, threshold {}",
+                    grid.rows(),
+                    grid.cols(),
+                    threshold
+                );
+                assert_eq!(accessible.count_ones(), count_accessible(&grid, &rules));
+            }
+        }
+    }
+
+    #[test]
+    fn peels_the_same_rounds_as_the_grid() {
+        for grid in test_grids() {
+            let bit_grid = BitGrid::from_grid(&grid, |cell| *cell == CellType::PaperRoll);
+
+            for threshold in THRESHOLDS {
+                let expected = removal_rounds(&grid, &rules(threshold))
+                    .iter()
+                    .map(|round| round.len())
+                    .collect::<Vec<_>>();
+
+                assert_eq!(
+                    bit_grid.peel(threshold),
+                    expected,
+                    "{}x{} grid, threshold {}",
+                    grid.rows(),
+                    grid.cols(),
+                    threshold
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn counts_every_neighbour_across_word_boundaries() {
+        // A full grid gives every count from 3 (corners) to 8 (inside)
+        for cols in WIDTHS {
+            let grid = Grid::new(3, cols, CellType::PaperRoll);
+            let bit_grid = BitGrid::from_grid(&grid, |cell| *cell == CellType::PaperRoll);
+
+            for threshold in THRESHOLDS {
+                assert_eq!(
+                    bit_grid.with_fewer_neighbours(threshold).count_ones(),
+                    count_accessible(&grid, &rules(threshold)),
+                    "3x{} grid, threshold {}",
+                    cols,
+                    threshold
+                );
+            }
+        }
+    }
+}
@@ -36,10 +36,9 @@ fn solve_part_1(input_filepath: &str) -> i32 {
         .map_while(|line| line.ok().filter(|x| !x.is_empty()))
     {
         let (direction, distance_str) = input_line.split_at(1);
-        let mut distance = distance_str.parse::<i32>().expect(&format!(
-            "Unable to parse distance value '{}'",
-            distance_str
-        ));
+        let mut distance = distance_str
+            .parse::<i32>()
+            .unwrap_or_else(|_| panic!("Unable to parse distance value '{}'", distance_str));
 
         // Ignore extra revolutions
         distance %= 100;
@@ -98,10 +97,9 @@ fn solve_part_2(input_filepath: &str) -> i32 {
         .map_while(|line| line.ok().filter(|x| !x.is_empty()))
     {
         let (direction, distance_str) = input_line.split_at(1);
-        let mut distance = distance_str.parse::<i32>().expect(&format!(
-            "Unable to parse distance value '{}'",
-            distance_str
-        ));
+        let mut distance = distance_str
+            .parse::<i32>()
+            .unwrap_or_else(|_| panic!("Unable to parse distance value '{}'", distance_str));
 
         // Count extra revolutions that are guaranteed to go past 0
         zeroes += distance / 100;
@@ -1,14 +1,11 @@
 use std::{
-    collections::HashSet,
-    env,
-    fs::File,
-    io::{BufRead, BufReader},
-    process,
+    collections::{BTreeMap, HashSet},
+    env, fmt, fs, process,
 };
 
-fn main() {
-    println!("----- Day 2 -----");
+use advent_of_code_2025::repeated_ids::{Rule, get_ending_number, get_starting_number};
 
+fn main() {
     let args: Vec<String> = env::args().collect();
     if args.len() < 2 {
         eprintln!("No input file specified");
@@ -16,215 +13,432 @@ fn main() {
     }
 
     let input_path = &args[1];
+    let options = Options::parse(&args[2..]).unwrap_or_else(|err| {
+        eprintln!("{}", err);
+        process::exit(1);
+    });
+
+    // Reports are printed on their own, so that they can be redirected straight into a file
+    if let Some(report_format) = options.report_format {
+        print_report(input_path, report_format);
+        return;
+    }
+
+    println!("----- Day 2 -----");
     println!("Input File: {}", input_path);
 
-    println!("Part 1: {}", solve_part_1(input_path));
-    println!("Part 2: {}", solve_part_2(input_path));
+    let part_1 = solve_part_1(input_path, options.keep_overlaps);
+    let part_2 = solve_part_2(input_path, options.keep_overlaps);
+
+    for (part, stats) in [(1, part_1), (2, part_2)] {
+        if options.count_only {
+            println!("Part {}: {}", part, stats.count);
+        } else {
+            println!("Part {}: {}", part, stats.sum);
+        }
+
+        if options.show_stats {
+            print_stats(&stats);
+        }
+    }
 }
 
-fn read_file_contents(path: &str) -> String {
-    let file = File::open(path).expect("Unable to open input file");
-    let mut input_reader = BufReader::new(file);
+#[derive(Clone, Copy, Debug)]
+enum ReportFormat {
+    Csv,
+    Json,
+}
 
-    let mut input_str = String::new();
-    input_reader
-        .read_line(&mut input_str)
-        .expect("No input lines found");
+#[derive(Default)]
+struct Options {
+    report_format: Option<ReportFormat>,
+    count_only: bool,
+    show_stats: bool,
+    keep_overlaps: bool,
+}
 
-    input_str
+impl Options {
+    fn parse(args: &[String]) -> Result<Self, String> {
+        let mut options = Options::default();
+        let mut args_iter = args.iter();
+
+        while let Some(arg) = args_iter.next() {
+            match arg.as_str() {
+                "--report" => {
+                    let format = args_iter.next().ok_or("Missing value for '--report'")?;
+                    options.report_format = match format.as_str() {
+                        "csv" => Some(ReportFormat::Csv),
+                        "json" => Some(ReportFormat::Json),
+                        _ => return Err(format!("Unknown report format '{}'", format)),
+                    };
+                }
+                "--count" => options.count_only = true,
+                "--stats" => options.show_stats = true,
+                "--keep-overlaps" => options.keep_overlaps = true,
+                _ => return Err(format!("Unknown option '{}'", arg)),
+            }
+        }
+
+        Ok(options)
+    }
 }
 
-fn parse_id_ranges<'a>(input: &'a str) -> impl Iterator<Item = (&'a str, &'a str)> {
-    input
-        .trim()
-        .split(",")
-        .filter(|s| !s.is_empty())
-        .filter_map(|s| s.split_once("-"))
+/// Inclusive range of IDs from the input list.
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct IdRange {
+    start: u64,
+    end: u64,
 }
 
-/// Determines which IDs contain 2 repeated halves, e.g. 113113, then sums them together.
-///
-/// **Answer**: `19128774598`
-fn solve_part_1(input_path: &str) -> u64 {
-    let input = read_file_contents(input_path);
-    let id_ranges_iter = parse_id_ranges(&input);
-
-    let mut total = 0;
-
-    for (range_start_str, range_end_str) in id_ranges_iter {
-        // println!("{}-{}", range_start_str, range_end_str);
-
-        // Determine where to start looking for invalid IDs
-        let start_digits = range_start_str.chars().count();
-        let start_middle_digit = start_digits / 2;
-
-        let invalid_start = if start_digits % 2 == 0 {
-            // Even Case: Split at middle digit
-            // - If left >= right, start at left, e.g. 1402 => start at 14
-            // - Otherwise, start at left + 1, e.g. 1425 => start at 15
-            let (left, right) = range_start_str.split_at(start_middle_digit);
-            let left_parsed = left.parse::<u32>().unwrap();
-
-            if left >= right {
-                left_parsed
-            } else {
-                left_parsed + 1
+impl IdRange {
+    /// Gets the range bounds as digit strings, which is what the chunk logic works with.
+    fn to_strings(&self) -> (String, String) {
+        (self.start.to_string(), self.end.to_string())
+    }
+}
+
+#[derive(Debug)]
+enum ParseErrorKind {
+    MissingSeparator(String),
+    InvalidNumber(String),
+}
+
+/// Problem found while parsing the range list, located by its character offset in the input.
+#[derive(Debug)]
+struct ParseError {
+    offset: usize,
+    kind: ParseErrorKind,
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.kind {
+            ParseErrorKind::MissingSeparator(entry) => write!(
+                f,
+                "Offset {}: Expected a range like '11-22', but found '{}'",
+                self.offset, entry
+            ),
+            ParseErrorKind::InvalidNumber(value) => {
+                write!(f, "Offset {}: '{}' is not a valid ID", self.offset, value)
             }
-        } else {
-            // Odd Case: Start at 10 ^ (middle digit index)
-            // e.g. 12345 => start at 100
-            10_u32.pow(start_middle_digit as u32)
+        }
+    }
+}
+
+/// Reads and parses the range list, exiting with all parsing problems if any were found.
+fn read_id_ranges(path: &str) -> Vec<IdRange> {
+    let input = fs::read_to_string(path).expect("Unable to open input file");
+
+    parse_id_ranges(&input).unwrap_or_else(|errors| {
+        for error in errors.iter() {
+            eprintln!("{}", error);
+        }
+
+        process::exit(1);
+    })
+}
+
+/// Sorts the ranges and merges any that overlap or touch, so that each ID is only covered once.
+fn merge_id_ranges(mut id_ranges: Vec<IdRange>) -> Vec<IdRange> {
+    id_ranges.sort_by_key(|id_range| id_range.start);
+
+    let mut merged_ranges: Vec<IdRange> = Vec::with_capacity(id_ranges.len());
+
+    for id_range in id_ranges {
+        match merged_ranges.last_mut() {
+            Some(last) if id_range.start <= last.end.saturating_add(1) => {
+                last.end = last.end.max(id_range.end);
+            }
+            _ => merged_ranges.push(id_range),
+        }
+    }
+
+    merged_ranges
+}
+
+/// Parses a list of ID ranges, which can be separated by commas and/or newlines.
+///
+/// - Whitespace around commas and dashes is ignored, as are empty entries
+/// - Leading zeros are dropped, e.g. `0011-0022` is the same as `11-22`
+/// - Reversed ranges are swapped around, e.g. `22-11` is the same as `11-22`
+/// - Duplicate ranges are kept as they are
+fn parse_id_ranges(input: &str) -> Result<Vec<IdRange>, Vec<ParseError>> {
+    let mut id_ranges = Vec::new();
+    let mut errors = Vec::new();
+
+    // Byte offset of the current entry, which is converted to a character offset when reporting
+    let mut entry_offset = 0;
+
+    let char_offset = |byte_offset: usize| input[..byte_offset].chars().count();
+    let trimmed_offset = |s: &str| s.len() - s.trim_start().len();
+
+    for entry in input.split([',', '\n']) {
+        let offset = entry_offset;
+        entry_offset += entry.len() + 1;
+
+        if entry.trim().is_empty() {
+            continue;
+        }
+
+        let Some((start_str, end_str)) = entry.split_once('-') else {
+            errors.push(ParseError {
+                offset: char_offset(offset + trimmed_offset(entry)),
+                kind: ParseErrorKind::MissingSeparator(entry.trim().to_string()),
+            });
+
+            continue;
         };
 
-        // Determine where to stop looking for invalid IDs
-        let end_digits = range_end_str.chars().count();
-        let end_middle_digit = end_digits / 2;
-
-        let invalid_end = if end_digits % 2 == 0 {
-            // Even Case: Split at middle digit
-            // - If left <= right, end at left, e.g. 1470 => end at 14
-            // - Otherwise, end at left - 1,    e.g. 1402 => end at 13
-            let (left, right) = range_end_str.split_at(end_middle_digit);
-            let left_parsed = left.parse::<u32>().unwrap();
-
-            if left <= right {
-                left_parsed
-            } else {
-                left_parsed - 1
+        // Bounds must be plain digits, so signs and nested dashes are rejected
+        let mut parse_bound = |bound_str: &str, bound_offset: usize| {
+            let bound = bound_str.trim();
+            let parsed = bound
+                .chars()
+                .all(|c| c.is_ascii_digit())
+                .then(|| bound.parse::<u64>().ok())
+                .flatten();
+
+            if parsed.is_none() {
+                errors.push(ParseError {
+                    offset: char_offset(bound_offset + trimmed_offset(bound_str)),
+                    kind: ParseErrorKind::InvalidNumber(bound.to_string()),
+                });
             }
-        } else {
-            // Odd Case: Start at 10 ^ (middle digit index) - 1
-            // e.g. 12345 => end at 99
-            10_u32.pow(end_middle_digit as u32) - 1
+
+            parsed
         };
 
-        for i in invalid_start..=invalid_end {
-            // NOTE: Can definitely do this without string parsing, too lazy LOL
-            let invalid_id = format!("{}{}", i, i).parse::<u64>().unwrap();
-            // println!("- Invalid: {}", invalid_id);
+        let start = parse_bound(start_str, offset);
+        let end = parse_bound(end_str, offset + start_str.len() + 1);
 
-            total += invalid_id;
+        if let (Some(start), Some(end)) = (start, end) {
+            id_ranges.push(IdRange {
+                start: start.min(end),
+                end: start.max(end),
+            });
         }
     }
 
-    total
+    if errors.is_empty() {
+        Ok(id_ranges)
+    } else {
+        Err(errors)
+    }
+}
+
+/// Aggregated values for a set of invalid IDs.
+#[derive(Debug, Default)]
+struct InvalidIdStats {
+    count: usize,
+    sum: u64,
+    min: Option<u64>,
+    max: Option<u64>,
+
+    /// Number of invalid IDs for each `(digit count, chunk count)` pair.
+    by_length: BTreeMap<(usize, usize), usize>,
+}
+
+impl InvalidIdStats {
+    /// Adds an invalid ID, which is made from `chunk_count` repeated chunks.
+    ///
+    /// If an ID can be made from several chunk counts, e.g. `2222`, use the largest one.
+    fn add(&mut self, invalid_id: u64, chunk_count: usize) {
+        self.count += 1;
+        self.sum += invalid_id;
+        self.min = Some(self.min.map_or(invalid_id, |min| min.min(invalid_id)));
+        self.max = Some(self.max.map_or(invalid_id, |max| max.max(invalid_id)));
+
+        let digit_count = invalid_id.to_string().len();
+        *self
+            .by_length
+            .entry((digit_count, chunk_count))
+            .or_default() += 1;
+    }
+}
+
+fn print_stats(stats: &InvalidIdStats) {
+    let display_or_dash = |value: Option<u64>| value.map_or("-".to_string(), |v| v.to_string());
+
+    println!("- Count: {}", stats.count);
+    println!("- Sum: {}", stats.sum);
+    println!("- Min: {}", display_or_dash(stats.min));
+    println!("- Max: {}", display_or_dash(stats.max));
+    println!("- Breakdown:");
+
+    for ((digit_count, chunk_count), count) in stats.by_length.iter() {
+        println!(
+            "  - {} digits, {} chunks: {}",
+            digit_count, chunk_count, count
+        );
+    }
+}
+
+/// Determines which IDs contain 2 repeated halves, e.g. 113113, then aggregates them.
+///
+/// Overlapping ranges are merged first, unless `keep_overlaps` is set, in which case IDs in the
+/// overlap are counted once per range.
+///
+/// **Answer**: `19128774598` (sum)
+fn solve_part_1(input_path: &str, keep_overlaps: bool) -> InvalidIdStats {
+    let mut id_ranges = read_id_ranges(input_path);
+    if !keep_overlaps {
+        id_ranges = merge_id_ranges(id_ranges);
+    }
+
+    let mut stats = InvalidIdStats::default();
+
+    for id_range in id_ranges.iter() {
+        for invalid_id in find_invalid_ids(id_range, Rule::Halves).into_keys() {
+            stats.add(invalid_id, 2);
+        }
+    }
+
+    stats
 }
 
 /// More generalised version of part 1, where it looks at repeating chunks of different sizes.
 ///
-/// **Answer**: `21932258645`
-fn solve_part_2(input_path: &str) -> u64 {
-    let input = read_file_contents(input_path);
-    let id_ranges_iter = parse_id_ranges(&input);
+/// Overlapping ranges are merged first, unless `keep_overlaps` is set. IDs are only counted once
+/// either way, so this just avoids enumerating the overlap twice.
+///
+/// **Answer**: `21932258645` (sum)
+fn solve_part_2(input_path: &str, keep_overlaps: bool) -> InvalidIdStats {
+    let mut id_ranges = read_id_ranges(input_path);
+    if !keep_overlaps {
+        id_ranges = merge_id_ranges(id_ranges);
+    }
 
     // Keep track of unique IDs, as duplicates can be encountered
     // e.g. 2222 is encountered when chunk counts are:
     // - 4: 2, 2, 2, 2
     // - 2: 22, 22
+    // This also covers overlapping ranges when they're kept as they are
     let mut invalid_ids: HashSet<u64> = HashSet::new();
+    let mut stats = InvalidIdStats::default();
 
     /*
      * Looking back at my solution, I think it became complicated because I iterated over chunk
      * count instead of chunk size. But it worked in the end, so ¯\_(ツ)_/¯
      */
-    for (range_start_str, range_end_str) in id_ranges_iter {
-        let range_start = range_start_str.parse::<u64>().unwrap();
-        let range_end = range_end_str.parse::<u64>().unwrap();
+    for id_range in id_ranges.iter() {
+        for (invalid_id, chunk_counts) in find_invalid_ids(id_range, Rule::RepeatedChunks) {
+            if invalid_ids.insert(invalid_id) {
+                stats.add(invalid_id, *chunk_counts.last().unwrap());
+            }
+        }
+    }
 
-        // println!("{}-{}", range_start_str, range_end_str);
-        let max_chunk_count = std::cmp::max(range_start_str.len(), range_end_str.len());
+    stats
+}
 
-        for chunk_count in 2..=max_chunk_count {
-            // println!("- Chunk Count: {}", chunk_count);
+/// Finds the IDs within a range that are invalid under `rule`, along with the chunk counts that
+/// produce each of them.
+///
+/// IDs are kept in ascending order, e.g. `2222` in `2200-2300` maps to chunk counts `[2, 4]`.
+fn find_invalid_ids(id_range: &IdRange, rule: Rule) -> BTreeMap<u64, Vec<usize>> {
+    let (range_start_str, range_end_str) = id_range.to_strings();
 
-            let invalid_start = get_starting_number(range_start_str, chunk_count);
-            let invalid_end = get_ending_number(range_end_str, chunk_count);
+    let mut invalid_ids: BTreeMap<u64, Vec<usize>> = BTreeMap::new();
+    let max_chunk_count = std::cmp::max(range_start_str.len(), range_end_str.len());
 
-            for i in invalid_start..=invalid_end {
-                let invalid_id_str = i.to_string().repeat(chunk_count);
-                let invalid_id = invalid_id_str.parse::<u64>().unwrap();
+    for chunk_count in rule.chunk_counts(max_chunk_count) {
+        let invalid_start = get_starting_number(&range_start_str, chunk_count);
+        let invalid_end = get_ending_number(&range_end_str, chunk_count);
 
-                if invalid_id >= range_start && invalid_id <= range_end {
-                    // println!("  - Invalid: {}", invalid_id);
-                    invalid_ids.insert(invalid_id);
-                }
+        for i in invalid_start..=invalid_end {
+            let invalid_id_str = i.to_string().repeat(chunk_count);
+            let invalid_id = invalid_id_str.parse::<u64>().unwrap();
+
+            if invalid_id >= id_range.start && invalid_id <= id_range.end {
+                invalid_ids.entry(invalid_id).or_default().push(chunk_count);
             }
         }
     }
 
-    invalid_ids.iter().sum()
+    invalid_ids
 }
 
-/// More generalised way of getting the starting number to check.
-fn get_starting_number(range_start: &str, chunk_count: usize) -> u32 {
-    /*
-     * Examples:
-     * - 12345678, Chunk Count 2
-     *   - Chunk Cutoff: Up to 4th Digit => 1234
-     *   - Chunks are split exactly
-     *     - 1st Chunk = 1234, 2nd Chunk = 5678
-     *     - 1st Chunk <= 2nd Chunk, so start at 1234 + 1
-     * - 12345678, Chunk Count 3
-     *   - Chunk Cutoff: Up to 2nd Digit => 12
-     *   - Chunks aren't split exactly
-     *     - Start at 10 ^ Cutoff => 10^2 = 100
-     */
-    let digit_count = range_start.chars().count();
-    let cutoff_idx = digit_count / chunk_count;
-
-    if digit_count % chunk_count == 0 {
-        // Exact Split Case
-        // - If 1st chunk >= 2nd chunk, start at 1st chunk
-        // - Otherwise, start at 1st chunk + 1
-        let chunk_1 = &range_start[0..cutoff_idx];
-        let chunk_2 = &range_start[cutoff_idx..(cutoff_idx * 2)];
-
-        let chunk_1_parsed = chunk_1.parse::<u32>().unwrap();
-        if chunk_1 >= chunk_2 {
-            chunk_1_parsed
-        } else {
-            chunk_1_parsed + 1
-        }
+/// Prints every invalid ID (using the part 2 rules) alongside the range it was found in.
+///
+/// Each ID lists the chunk sizes that produced it, e.g. `2222` is produced by chunk sizes `1` and `2`.
+fn print_report(input_path: &str, report_format: ReportFormat) {
+    let id_ranges = read_id_ranges(input_path);
+
+    if let ReportFormat::Csv = report_format {
+        println!("range_start,range_end,invalid_id,chunk_sizes,range_count,range_subtotal");
     } else {
-        // Non-Exact Split Case
-        // Start at 10 ^ Cutoff
-        10_u32.pow(cutoff_idx as u32)
+        println!("[");
     }
-}
 
-/// More generalised way of getting the ending number to check.
-fn get_ending_number(range_end: &str, chunk_count: usize) -> u32 {
-    /*
-     * Examples:
-     * - 12345678, Chunk Count 2
-     *   - Chunk Cutoff: Up to 4th Digit => 1234
-     *   - Chunks are split exactly
-     *     - 1st Chunk = 1234, 2nd Chunk = 5678
-     *     - 1st Chunk <= 2nd Chunk, so end at 1234
-     * - 12345678, Chunk Count 3
-     *   - Chunk Cutoff: Up to 2nd Digit => 12
-     *   - Chunks aren't split exactly
-     *     - End at 10 ^ Cutoff - 1 => 10^2 - 1 = 99
-     */
-    let digit_count = range_end.chars().count();
-    let cutoff_idx = digit_count / chunk_count;
-
-    if digit_count % chunk_count == 0 {
-        // Exact Split Case
-        // - If 1st chunk <= 2nd chunk, end at 1st chunk
-        // - Otherwise, end at 1st chunk - 1
-        let chunk_1 = &range_end[0..cutoff_idx];
-        let chunk_2 = &range_end[cutoff_idx..(cutoff_idx * 2)];
-
-        let chunk_1_parsed = chunk_1.parse::<u32>().unwrap();
-        if chunk_1 <= chunk_2 {
-            chunk_1_parsed
-        } else {
-            chunk_1_parsed - 1
+    let mut id_ranges_iter = id_ranges.iter().peekable();
+
+    while let Some(id_range) = id_ranges_iter.next() {
+        let invalid_ids = find_invalid_ids(id_range, Rule::RepeatedChunks);
+        let range_count = invalid_ids.len();
+        let range_subtotal = invalid_ids.keys().sum::<u64>();
+
+        // Chunk counts are recorded during enumeration, but chunk sizes are easier to read
+        let chunk_sizes = |id: &u64, chunk_counts: &[usize]| {
+            let digit_count = id.to_string().len();
+            chunk_counts
+                .iter()
+                .rev()
+                .map(|chunk_count| digit_count / chunk_count)
+                .collect::<Vec<_>>()
+        };
+
+        match report_format {
+            ReportFormat::Csv => {
+                if invalid_ids.is_empty() {
+                    // Still list the range, so that it shows up with a subtotal of 0
+                    println!("{},{},,,0,0", id_range.start, id_range.end);
+                }
+
+                for (id, chunk_counts) in invalid_ids.iter() {
+                    let chunk_sizes = chunk_sizes(id, chunk_counts)
+                        .iter()
+                        .map(|size| size.to_string())
+                        .collect::<Vec<_>>()
+                        .join(";");
+
+                    println!(
+                        "{},{},{},{},{},{}",
+                        id_range.start, id_range.end, id, chunk_sizes, range_count, range_subtotal
+                    );
+                }
+            }
+            ReportFormat::Json => {
+                let invalid_ids_json = invalid_ids
+                    .iter()
+                    .map(|(id, chunk_counts)| {
+                        format!(
+                            "{{\"id\": {}, \"chunk_sizes\": {:?}}}",
+                            id,
+                            chunk_sizes(id, chunk_counts)
+                        )
+                    })
+                    .collect::<Vec<_>>()
+                    .join(", ");
+
+                let separator = if id_ranges_iter.peek().is_some() {
+                    ","
+                } else {
+                    ""
+                };
+                println!(
+                    "  {{\"start\": {}, \"end\": {}, \"count\": {}, \"subtotal\": {}, \"invalid_ids\": [{}]}}{}",
+                    id_range.start,
+                    id_range.end,
+                    range_count,
+                    range_subtotal,
+                    invalid_ids_json,
+                    separator
+                );
+            }
         }
-    } else {
-        // Non-Exact Split Case
-        // End at 10 ^ Cutoff - 1
-        10_u32.pow(cutoff_idx as u32) - 1
+    }
+
+    if let ReportFormat::Json = report_format {
+        println!("]");
     }
 }
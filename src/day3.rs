@@ -1,13 +1,8 @@
-use std::{
-    env,
-    fs::File,
-    io::{BufRead, BufReader},
-    process,
-};
+use std::{env, fmt, fs, num::NonZeroUsize, process, thread};
 
-fn main() {
-    println!("----- Day 3 -----");
+use advent_of_code_2025::batteries::{Constraints, Joltage, Objective, SelectionError, select};
 
+fn main() {
     let args: Vec<String> = env::args().collect();
     if args.len() < 2 {
         eprintln!("No input file specified");
@@ -15,109 +10,489 @@ fn main() {
     }
 
     let input_path = &args[1];
+    let options = Options::parse(&args[2..]).unwrap_or_else(|err| {
+        eprintln!("{}", err);
+        process::exit(1);
+    });
+
+    let exit_on_error = |err: BankError| -> ! {
+        eprintln!("{}", err);
+        process::exit(1);
+    };
+
+    let banks = read_banks(input_path);
+
+    // Either solve both parts, or use the battery count that was asked for
+    let runs = match options.battery_count {
+        Some(battery_count) => vec![(format!("{} Batteries", battery_count), battery_count)],
+        None => vec![
+            ("Part 1".to_string(), PART_1_BATTERY_COUNT),
+            ("Part 2".to_string(), PART_2_BATTERY_COUNT),
+        ],
+    };
+
+    if options.json {
+        for (_, battery_count) in runs {
+            for selection in select_batteries_parallel(
+                &banks,
+                battery_count,
+                &options.objective,
+                options.constraints,
+                options.thread_count,
+            )
+            .unwrap_or_else(|err| exit_on_error(err))
+            {
+                println!("{}", selection.to_json());
+            }
+        }
+
+        return;
+    }
+
+    println!("----- Day 3 -----");
     println!("Input File: {}", input_path);
 
-    println!("Part 1: {}", solve_part_1(input_path));
-    println!("Part 2: {}", solve_part_2(input_path));
+    for (label, battery_count) in runs {
+        let selections = select_batteries_parallel(
+            &banks,
+            battery_count,
+            &options.objective,
+            options.constraints,
+            options.thread_count,
+        )
+        .unwrap_or_else(|err| exit_on_error(err));
+
+        if options.show_selections {
+            for selection in selections.iter() {
+                println!("{}", selection);
+            }
+        }
+
+        let total = total_joltage(&selections).unwrap_or_else(|err| exit_on_error(err));
+        println!("{}: {}", label, total);
+
+        // Only show subtotals when the banks are actually grouped into racks
+        if banks.iter().any(|bank| bank.rack.is_some()) {
+            for (rack, subtotal) in
+                rack_subtotals(&selections).unwrap_or_else(|err| exit_on_error(err))
+            {
+                let rack_name = rack.unwrap_or("(no rack)");
+                println!("- {}: {}", rack_name, subtotal);
+            }
+        }
+    }
+}
+
+/// Number of batteries enabled in each bank for part 1.
+///
+/// **Answer**: `17196` (total of the largest joltages)
+const PART_1_BATTERY_COUNT: usize = 2;
+
+/// Number of batteries enabled in each bank for part 2.
+///
+/// **Answer**: `171039099596062` (total of the largest joltages)
+const PART_2_BATTERY_COUNT: usize = 12;
+
+struct Options {
+    battery_count: Option<usize>,
+    objective: Objective,
+    constraints: Constraints,
+    show_selections: bool,
+    json: bool,
+    thread_count: NonZeroUsize,
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Options {
+            battery_count: None,
+            objective: Objective::default(),
+            constraints: Constraints::default(),
+            show_selections: false,
+            json: false,
+            thread_count: NonZeroUsize::MIN,
+        }
+    }
+}
+
+impl Options {
+    fn parse(args: &[String]) -> Result<Self, String> {
+        let mut options = Options::default();
+        let mut args_iter = args.iter();
+
+        while let Some(arg) = args_iter.next() {
+            match arg.as_str() {
+                "--batteries" => {
+                    let count = args_iter.next().ok_or("Missing value for '--batteries'")?;
+                    let count = count
+                        .parse::<usize>()
+                        .ok()
+                        .filter(|count| *count > 0)
+                        .ok_or(format!("Invalid battery count '{}'", count))?;
+
+                    options.battery_count = Some(count);
+                }
+                "--min" => options.objective = Objective::Min,
+                "--target" => {
+                    let target = args_iter.next().ok_or("Missing value for '--target'")?;
+                    let target_digits = target
+                        .chars()
+                        .map(|c| c.to_digit(10).map(|d| d as u8))
+                        .collect::<Option<Vec<_>>>()
+                        .filter(|digits| !digits.is_empty())
+                        .ok_or(format!("Invalid target joltage '{}'", target))?;
+
+                    options.objective = Objective::Closest(target_digits);
+                }
+                "--no-adjacent" => options.constraints.no_adjacent = true,
+                "--group-size" => {
+                    let size = args_iter.next().ok_or("Missing value for '--group-size'")?;
+                    let size = size
+                        .parse::<usize>()
+                        .ok()
+                        .filter(|size| *size > 0)
+                        .ok_or(format!("Invalid group size '{}'", size))?;
+
+                    options.constraints.group_size = Some(size);
+                }
+                "--show" => options.show_selections = true,
+                "--json" => options.json = true,
+                "--threads" => {
+                    let count = args_iter.next().ok_or("Missing value for '--threads'")?;
+
+                    options.thread_count = if count == "auto" {
+                        thread::available_parallelism().unwrap_or(NonZeroUsize::MIN)
+                    } else {
+                        count
+                            .parse::<NonZeroUsize>()
+                            .map_err(|_| format!("Invalid thread count '{}'", count))?
+                    };
+                }
+                _ => return Err(format!("Unknown option '{}'", arg)),
+            }
+        }
+
+        Ok(options)
+    }
+}
+
+/// Problem with a specific bank in the input file.
+#[derive(Debug)]
+enum BankError {
+    /// Batteries couldn't be selected from the bank.
+    Selection {
+        line_number: usize,
+        error: SelectionError,
+    },
+
+    /// The bank's joltage is too large to store.
+    JoltageOverflow { line_number: usize },
+
+    /// Adding the bank's joltage made the total too large to store.
+    TotalOverflow { line_number: usize },
+}
+
+impl fmt::Display for BankError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        const HINT: &str = "enable the 'big-joltage' feature for larger values";
+
+        match self {
+            BankError::Selection { line_number, error } => {
+                write!(f, "Line {}: {}", line_number, error)
+            }
+            BankError::JoltageOverflow { line_number } => write!(
+                f,
+                "Line {}: Joltage doesn't fit in 64 bits ({})",
+                line_number, HINT
+            ),
+            BankError::TotalOverflow { line_number } => write!(
+                f,
+                "Line {}: Total joltage doesn't fit in 64 bits ({})",
+                line_number, HINT
+            ),
+        }
+    }
+}
+
+/// Bank of batteries from the input file.
+#[derive(Debug)]
+struct Bank {
+    line_number: usize,
+
+    /// Name of the rack that the bank is in, if the input is grouped into racks.
+    rack: Option<String>,
+    digits: Vec<u8>,
 }
 
-fn read_non_empty_lines(path: &str) -> impl Iterator<Item = String> {
-    let file = File::open(path).expect("Unable to open input file");
-    let reader = BufReader::new(file);
+/// Character in a bank that isn't a digit, at a line and column starting from 1.
+#[derive(Debug)]
+struct ParseError {
+    line_number: usize,
+    column: usize,
+    character: char,
+}
 
-    reader
-        .lines()
-        .filter_map(|result| result.ok().filter(|line| !line.is_empty()))
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Line {}, column {}: Expected a digit, but found {:?}",
+            self.line_number, self.column, self.character
+        )
+    }
 }
 
-/// Finds the largest possible joltage from each bank when only two batteries are enabled.
+fn read_banks(path: &str) -> Vec<Bank> {
+    let input = fs::read_to_string(path).expect("Unable to open input file");
+
+    parse_banks(&input).unwrap_or_else(|errors| {
+        for error in errors.iter() {
+            eprintln!("{}", error);
+        }
+
+        process::exit(1);
+    })
+}
+
+/// Parses banks of batteries, one per line.
 ///
-/// **Answer**: `17196`
-fn solve_part_1(input_path: &str) -> u32 {
-    let input_lines = read_non_empty_lines(input_path);
-    let mut total = 0;
-
-    for input_line in input_lines {
-        let digits = input_line
-            .chars()
-            .map(|c| c.to_digit(10).unwrap())
-            .collect::<Vec<_>>();
+/// - Windows line endings and whitespace around each bank are ignored, as are empty lines
+/// - Banks can be grouped into racks by putting a `[Rack Name]` line before them
+fn parse_banks(input: &str) -> Result<Vec<Bank>, Vec<ParseError>> {
+    let mut banks = Vec::new();
+    let mut errors = Vec::new();
+    let mut rack: Option<String> = None;
+
+    // NOTE: str::lines() also strips the \r from Windows line endings
+    for (line_idx, line) in input.lines().enumerate() {
+        let line_number = line_idx + 1;
+        let trimmed_line = line.trim();
+
+        if trimmed_line.is_empty() {
+            continue;
+        }
+
+        if let Some(rack_name) = trimmed_line
+            .strip_prefix('[')
+            .and_then(|l| l.strip_suffix(']'))
+        {
+            rack = Some(rack_name.trim().to_string());
+            continue;
+        }
+
+        // Columns count characters in the original line, so leading whitespace is included
+        let leading_chars = line.chars().count() - line.trim_start().chars().count();
+        let mut digits = Vec::with_capacity(trimmed_line.len());
 
-        let n = digits.len();
+        for (char_idx, c) in trimmed_line.chars().enumerate() {
+            match c.to_digit(10) {
+                Some(digit) => digits.push(digit as u8),
+                None => errors.push(ParseError {
+                    line_number,
+                    column: leading_chars + char_idx + 1,
+                    character: c,
+                }),
+            }
+        }
 
-        // Find lowest index x of largest digit between [0, n-1]
-        let (x, first_digit) = digits
+        banks.push(Bank {
+            line_number,
+            rack: rack.clone(),
+            digits,
+        });
+    }
+
+    if errors.is_empty() {
+        Ok(banks)
+    } else {
+        Err(errors)
+    }
+}
+
+/// Batteries that were enabled in a bank.
+#[derive(Debug)]
+struct BankSelection<'a> {
+    bank: &'a Bank,
+
+    /// Positions of the enabled batteries, in ascending order (starting at 0).
+    positions: Vec<usize>,
+    joltage: Joltage,
+}
+
+impl BankSelection<'_> {
+    /// Formats the selection as a single line of JSON.
+    fn to_json(&self) -> String {
+        let bank = self
+            .bank
+            .digits
             .iter()
-            .enumerate()
-            .take(n - 1)
-            .min_by(|(idx1, digit1), (idx2, digit2)| {
-                digit1.cmp(digit2).reverse().then(idx1.cmp(idx2))
-            })
-            .unwrap();
+            .map(|d| d.to_string())
+            .collect::<String>();
+
+        let rack = match &self.bank.rack {
+            Some(rack) => json_string(rack),
+            None => "null".to_string(),
+        };
 
-        // Find largest digit between [x+1, n]
-        let second_digit = digits.iter().skip(x + 1).max().unwrap();
+        format!(
+            "{{\"line\": {}, \"rack\": {}, \"batteries\": {}, \"bank\": \"{}\", \"positions\": {:?}, \"joltage\": {}}}",
+            self.bank.line_number,
+            rack,
+            self.positions.len(),
+            bank,
+            self.positions,
+            self.joltage
+        )
+    }
+}
+
+/// Quotes a string for JSON, escaping quotes, backslashes and control characters.
+fn json_string(value: &str) -> String {
+    let mut quoted = String::with_capacity(value.len() + 2);
+    quoted.push('"');
+
+    for c in value.chars() {
+        match c {
+            '"' => quoted.push_str("\\\""),
+            '\\' => quoted.push_str("\\\\"),
+            c if (c as u32) < 0x20 => quoted.push_str(&format!("\\u{:04x}", c as u32)),
+            c => quoted.push(c),
+        }
+    }
+
+    quoted.push('"');
+    quoted
+}
+
+impl fmt::Display for BankSelection<'_> {
+    /// Shows the bank with a marker underneath each enabled battery, e.g.
+    ///
+    /// ```text
+    /// Line 1: 98
+    ///   987654321111111
+    ///   ^^
+    /// ```
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let bank = self
+            .bank
+            .digits
+            .iter()
+            .map(|d| d.to_string())
+            .collect::<String>();
+
+        let mut markers = vec![' '; self.bank.digits.len()];
+        for &idx in self.positions.iter() {
+            markers[idx] = '^';
+        }
+
+        writeln!(f, "Line {}: {}", self.bank.line_number, self.joltage)?;
+        writeln!(f, "  {}", bank)?;
+        write!(f, "  {}", markers.iter().collect::<String>().trim_end())
+    }
+}
+
+/// Finds the batteries that best meet the objective in each bank, when `battery_count` batteries
+/// are enabled. By default, this is the largest possible joltage.
+fn select_batteries<'a>(
+    banks: &'a [Bank],
+    battery_count: usize,
+    objective: &Objective,
+    constraints: Constraints,
+) -> Result<Vec<BankSelection<'a>>, BankError> {
+    let mut selections = Vec::with_capacity(banks.len());
+
+    for bank in banks.iter() {
+        let line_number = bank.line_number;
+        let digits = &bank.digits;
+        let positions = select(digits, battery_count, objective, constraints)
+            .map_err(|error| BankError::Selection { line_number, error })?;
 
-        let max_bank_joltage = (*first_digit * 10) + *second_digit;
-        // println!(
-        //     "{} -> {}{} ({})",
-        //     input_line, *first_digit, *second_digit, max_bank_joltage
-        // );
+        let joltage = Joltage::from_digits(positions.iter().map(|&idx| digits[idx]))
+            .ok_or(BankError::JoltageOverflow { line_number })?;
 
-        total += max_bank_joltage;
+        selections.push(BankSelection {
+            bank,
+            positions,
+            joltage,
+        });
     }
 
-    total
+    Ok(selections)
 }
 
-/// Finds the largest possible joltage from each bank when 12 batteries are enabled.
+/// Same as [`select_batteries`], but splits the banks into contiguous shards that are processed on
+/// separate threads.
 ///
-/// **Answer**: `171039099596062`
-fn solve_part_2(input_path: &str) -> u64 {
-    let input_lines = read_non_empty_lines(input_path);
-    let mut total = 0;
+/// The shards are joined back together in order, so the selections (and the first error, if
+/// there is one) are exactly the same as processing the banks sequentially.
+fn select_batteries_parallel<'a>(
+    banks: &'a [Bank],
+    battery_count: usize,
+    objective: &Objective,
+    constraints: Constraints,
+    thread_count: NonZeroUsize,
+) -> Result<Vec<BankSelection<'a>>, BankError> {
+    if thread_count.get() == 1 || banks.len() <= 1 {
+        return select_batteries(banks, battery_count, objective, constraints);
+    }
 
-    const BATTERY_COUNT: usize = 12;
+    let shard_size = banks.len().div_ceil(thread_count.get());
 
-    for input_line in input_lines {
-        let digits = input_line
-            .chars()
-            .map(|c| c.to_digit(10).unwrap() as u64)
+    thread::scope(|scope| {
+        let handles = banks
+            .chunks(shard_size)
+            .map(|shard| {
+                scope.spawn(move || select_batteries(shard, battery_count, objective, constraints))
+            })
             .collect::<Vec<_>>();
 
-        let n = digits.len();
-
-        let mut battery_joltage = 0;
-        let mut previous_idx: Option<usize> = None;
-
-        for i in (0..BATTERY_COUNT).rev() {
-            // If first selection, start at 0, otherwise prev + 1
-            // Then leave enough space at the end for subsequent batteries
-            let start_idx = previous_idx.map(|idx| idx + 1).unwrap_or(0);
-            let end_idx = n - i;
-
-            // Find lowest index of largest digit between [start, end]
-            let (idx, digit) = digits
-                .iter()
-                .enumerate()
-                .take(end_idx)
-                .skip(start_idx)
-                .min_by(|(idx1, digit1), (idx2, digit2)| {
-                    digit1.cmp(digit2).reverse().then(idx1.cmp(idx2))
-                })
-                .unwrap();
+        let mut selections = Vec::with_capacity(banks.len());
 
-            previous_idx = Some(idx);
-            battery_joltage = (battery_joltage * 10) + *digit;
+        for handle in handles {
+            let shard_selections = handle.join().expect("Bank selection thread panicked")?;
+            selections.extend(shard_selections);
         }
 
-        // println!(
-        //     "{} -> {}",
-        //     input_line, battery_joltage
-        // );
+        Ok(selections)
+    })
+}
+
+/// Sums the joltages from each bank.
+fn total_joltage(selections: &[BankSelection]) -> Result<Joltage, BankError> {
+    selections
+        .iter()
+        .try_fold(Joltage::default(), |total, selection| {
+            total
+                .checked_add(&selection.joltage)
+                .ok_or(BankError::TotalOverflow {
+                    line_number: selection.bank.line_number,
+                })
+        })
+}
+
+/// Sums the joltages from each rack, in the order that the racks first appear.
+fn rack_subtotals<'a>(
+    selections: &[BankSelection<'a>],
+) -> Result<Vec<(Option<&'a str>, Joltage)>, BankError> {
+    let mut subtotals: Vec<(Option<&str>, Joltage)> = Vec::new();
+
+    for selection in selections.iter() {
+        let rack = selection.bank.rack.as_deref();
+        let subtotal_idx = match subtotals.iter().position(|(r, _)| *r == rack) {
+            Some(idx) => idx,
+            None => {
+                subtotals.push((rack, Joltage::default()));
+                subtotals.len() - 1
+            }
+        };
 
-        total += battery_joltage;
+        let subtotal = &mut subtotals[subtotal_idx].1;
+        *subtotal = subtotal
+            .checked_add(&selection.joltage)
+            .ok_or(BankError::TotalOverflow {
+                line_number: selection.bank.line_number,
+            })?;
     }
 
-    total
+    Ok(subtotals)
 }
@@ -0,0 +1,106 @@
+//! Animates the roll removal rounds in the terminal, using plain ANSI escape codes.
+
+use std::{
+    io::{self, BufRead, Write},
+    thread,
+    time::Duration,
+};
+
+use advent_of_code_2025::paper_rolls::{Removal, RemovalTimeline};
+
+/// Clears the screen and moves the cursor back to the top left.
+const CLEAR_SCREEN: &str = "\x1b[2J\x1b[H";
+
+/// Bold red, for the rolls removed in the current round.
+const HIGHLIGHT: &str = "\x1b[1;31m";
+
+const RESET: &str = "\x1b[0m";
+
+/// How to move from one frame to the next.
+pub enum Pacing {
+    /// Waits for a fixed time between frames.
+    Delay(Duration),
+
+    /// Waits for Enter to be pressed between frames.
+    Step,
+}
+
+/// Shows each removal round in turn, then the rolls that are left at the end.
+///
+/// Each frame shows the rolls that are still there at the start of the round, with the rolls that
+/// are about to be removed highlighted.
+pub fn animate(timeline: &RemovalTimeline, pacing: &Pacing) -> io::Result<()> {
+    let round_count = timeline.round_counts.len();
+    let mut removed_so_far = 0;
+
+    for (round_idx, count) in timeline.round_counts.iter().enumerate() {
+        let round = round_idx + 1;
+        removed_so_far += count;
+
+        let title = format!(
+            "Round {}/{}: Removing {} rolls ({} removed so far)",
+            round, round_count, count, removed_so_far
+        );
+        show_frame(&title, &render_round(timeline, round))?;
+
+        if !wait(pacing)? {
+            return Ok(());
+        }
+    }
+
+    let title = format!(
+        "Done: Removed {} rolls in {} rounds",
+        timeline.total_removed(),
+        round_count
+    );
+    show_frame(&title, &render_round(timeline, round_count + 1))
+}
+
+/// Renders the grid at the start of `round` (starting at 1).
+fn render_round(timeline: &RemovalTimeline, round: usize) -> String {
+    let mut output = String::new();
+
+    for row in timeline.removals.iter_rows() {
+        for removal in row {
+            match removal {
+                Removal::Round(removed_round) if *removed_round == round => {
+                    output.push_str(HIGHLIGHT);
+                    output.push('@');
+                    output.push_str(RESET);
+                }
+                Removal::Round(removed_round) if *removed_round > round => output.push('@'),
+                Removal::Never => output.push('@'),
+                _ => output.push('.'),
+            }
+        }
+
+        output.push('\n');
+    }
+
+    output
+}
+
+/// Draws a frame in one go, to avoid flickering.
+fn show_frame(title: &str, grid: &str) -> io::Result<()> {
+    let mut stdout = io::stdout().lock();
+    write!(stdout, "{}{}\n\n{}", CLEAR_SCREEN, title, grid)?;
+    stdout.flush()
+}
+
+/// Waits before the next frame. Returns `false` if there's no more input to step through.
+fn wait(pacing: &Pacing) -> io::Result<bool> {
+    match pacing {
+        Pacing::Delay(delay) => {
+            thread::sleep(*delay);
+            Ok(true)
+        }
+        Pacing::Step => {
+            print!("\nPress Enter for the next round...");
+            io::stdout().flush()?;
+
+            let mut line = String::new();
+            let bytes_read = io::stdin().lock().read_line(&mut line)?;
+            Ok(bytes_read > 0)
+        }
+    }
+}
@@ -0,0 +1,169 @@
+//! Places and removes rolls one at a time, from a file of edits or typed in at a prompt.
+
+use std::io::{self, BufRead, Write};
+
+use advent_of_code_2025::paper_rolls::{CellType, LiveGrid};
+
+const HELP: &str = "\
+Commands:
+- add R,C: Places a roll at row R, column C
+- remove R,C: Removes the roll at row R, column C
+- accessible: Lists the accessible rolls
+- show: Shows the map, with the accessible rolls as 'x'
+- help: Shows this list
+- quit: Stops reading commands";
+
+/// A single line of edits.
+enum Command {
+    Add(usize, usize),
+    Remove(usize, usize),
+    Accessible,
+    Show,
+    Help,
+    Quit,
+}
+
+impl Command {
+    /// Parses a command, or returns `None` for blank lines and comments (starting with `#`).
+    fn parse(line: &str) -> Result<Option<Self>, String> {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            return Ok(None);
+        }
+
+        let (name, argument) = match line.split_once(char::is_whitespace) {
+            Some((name, argument)) => (name, Some(argument.trim())),
+            None => (line, None),
+        };
+
+        let command = match (name, argument) {
+            ("add", Some(position)) => {
+                let (row, col) = parse_position(position)?;
+                Command::Add(row, col)
+            }
+            ("remove", Some(position)) => {
+                let (row, col) = parse_position(position)?;
+                Command::Remove(row, col)
+            }
+            ("add" | "remove", None) => return Err(format!("Missing position for '{}'", name)),
+            ("accessible", None) => Command::Accessible,
+            ("show", None) => Command::Show,
+            ("help", None) => Command::Help,
+            ("quit", None) => Command::Quit,
+            _ => return Err(format!("Unknown command '{}'", line)),
+        };
+
+        Ok(Some(command))
+    }
+}
+
+fn parse_position(value: &str) -> Result<(usize, usize), String> {
+    value
+        .split_once(',')
+        .and_then(|(row, col)| {
+            Some((
+                row.trim().parse::<usize>().ok()?,
+                col.trim().parse::<usize>().ok()?,
+            ))
+        })
+        .ok_or(format!("Invalid position '{}', expected 'row,col'", value))
+}
+
+/// Runs each line of `input` as a command, printing the accessible count after each edit.
+///
+/// When `interactive`, a prompt is shown before each line and mistakes are reported without
+/// stopping. Otherwise, the first mistake stops the edits and is returned with its line number.
+pub fn run_edits(
+    live_grid: &mut LiveGrid,
+    input: impl BufRead,
+    interactive: bool,
+) -> Result<(), String> {
+    let mut lines = input.lines().enumerate();
+
+    loop {
+        if interactive {
+            print!("> ");
+            io::stdout()
+                .flush()
+                .expect("Unable to write to the terminal");
+        }
+
+        let Some((line_idx, line)) = lines.next() else {
+            break;
+        };
+        let line = line.map_err(|err| format!("Unable to read edits: {}", err))?;
+
+        match Command::parse(&line).and_then(|command| run_command(live_grid, command)) {
+            Ok(true) => {}
+            Ok(false) => break,
+            Err(err) if interactive => println!("{}", err),
+            Err(err) => return Err(format!("Line {}: {}", line_idx + 1, err)),
+        }
+    }
+
+    Ok(())
+}
+
+/// Runs a command, returning `false` if there shouldn't be any more commands.
+fn run_command(live_grid: &mut LiveGrid, command: Option<Command>) -> Result<bool, String> {
+    match command {
+        None => {}
+        Some(Command::Add(row, col)) => {
+            live_grid
+                .place_roll(row, col)
+                .map_err(|err| err.to_string())?;
+
+            println!(
+                "Added ({}, {}): {} accessible rolls",
+                row,
+                col,
+                live_grid.accessible_count()
+            );
+        }
+        Some(Command::Remove(row, col)) => {
+            live_grid
+                .remove_roll(row, col)
+                .map_err(|err| err.to_string())?;
+
+            println!(
+                "Removed ({}, {}): {} accessible rolls",
+                row,
+                col,
+                live_grid.accessible_count()
+            );
+        }
+        Some(Command::Accessible) => {
+            let accessible = live_grid.accessible();
+
+            println!("Accessible Rolls: {}", accessible.len());
+            for (row_idx, col_idx) in accessible {
+                println!("- ({}, {})", row_idx, col_idx);
+            }
+        }
+        Some(Command::Show) => print!("{}", render(live_grid)),
+        Some(Command::Help) => println!("{}", HELP),
+        Some(Command::Quit) => return Ok(false),
+    }
+
+    Ok(true)
+}
+
+/// Renders the map, marking the accessible rolls with `x` (like the puzzle description).
+fn render(live_grid: &LiveGrid) -> String {
+    let mut output = String::new();
+
+    for (row_idx, row) in live_grid.to_grid().iter_rows().enumerate() {
+        for (col_idx, cell) in row.iter().enumerate() {
+            match cell {
+                CellType::PaperRoll if live_grid.is_accessible(row_idx, col_idx) => {
+                    output.push('x')
+                }
+                _ => output.push_str(&cell.to_string()),
+            }
+        }
+
+        output.push('\n');
+    }
+
+    output
+}
@@ -0,0 +1,167 @@
+//! Exports the grid and its removal rounds as image files.
+
+use std::{
+    fs::{self, File},
+    io::{self, BufWriter},
+    path::Path,
+};
+
+use advent_of_code_2025::{
+    image::{Image, Rgb},
+    paper_rolls::{Removal, RemovalTimeline},
+};
+
+const EMPTY_COLOUR: Rgb = [24, 24, 24];
+const ROLL_COLOUR: Rgb = [160, 160, 160];
+const REMOVING_COLOUR: Rgb = [220, 40, 40];
+const STABLE_COLOUR: Rgb = [255, 255, 255];
+
+/// Colours for the first and last removal rounds, when colouring by round.
+const FIRST_ROUND_COLOUR: Rgb = [255, 210, 0];
+const LAST_ROUND_COLOUR: Rgb = [30, 60, 200];
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum ImageFormat {
+    Ppm,
+    #[cfg(feature = "png")]
+    Png,
+}
+
+impl ImageFormat {
+    pub fn parse(value: &str) -> Result<Self, String> {
+        match value {
+            "ppm" => Ok(ImageFormat::Ppm),
+            #[cfg(feature = "png")]
+            "png" => Ok(ImageFormat::Png),
+            #[cfg(not(feature = "png"))]
+            "png" => Err("PNG export needs the 'png' feature".to_string()),
+            _ => Err(format!("Unknown image format '{}'", value)),
+        }
+    }
+
+    const fn extension(self) -> &'static str {
+        match self {
+            ImageFormat::Ppm => "ppm",
+            #[cfg(feature = "png")]
+            ImageFormat::Png => "png",
+        }
+    }
+}
+
+/// What the colour of each cell shows.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum ColourBy {
+    /// Whether there's a roll, and whether it gets removed.
+    #[default]
+    State,
+
+    /// The round each roll gets removed in, from yellow (first) to blue (last).
+    Round,
+}
+
+impl ColourBy {
+    pub fn parse(value: &str) -> Result<Self, String> {
+        match value {
+            "state" => Ok(ColourBy::State),
+            "round" => Ok(ColourBy::Round),
+            _ => Err(format!("Unknown colouring '{}'", value)),
+        }
+    }
+}
+
+/// Settings shared by every exported image.
+pub struct ExportStyle {
+    pub format: ImageFormat,
+    pub colour_by: ColourBy,
+
+    /// Width and height of each cell, in pixels.
+    pub scale: usize,
+}
+
+/// Writes the whole grid as a single image.
+///
+/// When colouring by state, rolls that get removed are grey and rolls that are never removed are
+/// white.
+pub fn write_image(timeline: &RemovalTimeline, style: &ExportStyle, path: &Path) -> io::Result<()> {
+    write_frame(timeline, style, None, path)
+}
+
+/// Writes one image per round into `dir`, then a final image of the rolls that are left.
+///
+/// The images are named by round (e.g. `round_01.ppm`), so they sort into the right order.
+/// Returns how many images were written.
+pub fn write_frames(
+    timeline: &RemovalTimeline,
+    style: &ExportStyle,
+    dir: &Path,
+) -> io::Result<usize> {
+    fs::create_dir_all(dir)?;
+
+    let frame_count = timeline.round_counts.len() + 1;
+    let width = frame_count.to_string().len();
+
+    for round in 1..=frame_count {
+        let file_name = format!(
+            "round_{:0width$}.{}",
+            round,
+            style.format.extension(),
+            width = width
+        );
+        write_frame(timeline, style, Some(round), &dir.join(file_name))?;
+    }
+
+    Ok(frame_count)
+}
+
+/// Writes the grid as it is at the start of `round`, or the initial grid if there's no round.
+fn write_frame(
+    timeline: &RemovalTimeline,
+    style: &ExportStyle,
+    round: Option<usize>,
+    path: &Path,
+) -> io::Result<()> {
+    let round_count = timeline.round_counts.len();
+    let image = Image::from_grid(&timeline.removals, style.scale, |removal| {
+        cell_colour(*removal, round, style.colour_by, round_count)
+    });
+
+    let writer = BufWriter::new(File::create(path)?);
+
+    match style.format {
+        ImageFormat::Ppm => image.write_ppm(writer),
+        #[cfg(feature = "png")]
+        ImageFormat::Png => image.write_png(writer),
+    }
+}
+
+fn cell_colour(
+    removal: Removal,
+    round: Option<usize>,
+    colour_by: ColourBy,
+    round_count: usize,
+) -> Rgb {
+    match (removal, round) {
+        (Removal::NoRoll, _) => EMPTY_COLOUR,
+        (Removal::Round(removed_round), Some(round)) if removed_round < round => EMPTY_COLOUR,
+        (Removal::Never, _) if colour_by == ColourBy::Round => STABLE_COLOUR,
+        (Removal::Round(removed_round), _) if colour_by == ColourBy::Round => {
+            round_colour(removed_round, round_count)
+        }
+        (Removal::Round(removed_round), Some(round)) if removed_round == round => REMOVING_COLOUR,
+        (_, Some(_)) => ROLL_COLOUR,
+        (Removal::Never, None) => STABLE_COLOUR,
+        (Removal::Round(_), None) => ROLL_COLOUR,
+    }
+}
+
+/// Blends between the first and last round colours.
+fn round_colour(round: usize, round_count: usize) -> Rgb {
+    let last_idx = round_count.saturating_sub(1).max(1) as f64;
+    let t = (round - 1) as f64 / last_idx;
+
+    std::array::from_fn(|channel| {
+        let start = FIRST_ROUND_COLOUR[channel] as f64;
+        let end = LAST_ROUND_COLOUR[channel] as f64;
+        (start + ((end - start) * t)).round() as u8
+    })
+}
@@ -0,0 +1,682 @@
+mod animation;
+mod edits;
+mod export;
+
+use std::{
+    env, fs,
+    io::{self, BufReader},
+    path::PathBuf,
+    process,
+    time::Duration,
+};
+
+use advent_of_code_2025::{
+    bit_grid::BitGrid,
+    grid::Grid,
+    lattice::{self, CubicLattice, HexLattice, Topology},
+    paper_rolls::{
+        CellType, EdgeMode, LiveGrid, Neighbourhood, NeighbourhoodShape, Removal, RemovalTimeline,
+        Rules, count_accessible, count_accessible_in, removal_rounds, removal_rounds_in,
+    },
+    roll_queries,
+    sparse_rolls::SparseRolls,
+};
+
+use animation::Pacing;
+use export::{ColourBy, ExportStyle, ImageFormat};
+
+fn main() {
+    let args: Vec<String> = env::args().collect();
+    if args.len() < 2 {
+        eprintln!("No input file specified");
+        process::exit(1);
+    }
+
+    let input_path = &args[1];
+    let options = Options::parse(&args[2..]).unwrap_or_else(|err| {
+        eprintln!("{}", err);
+        process::exit(1);
+    });
+
+    if options.json {
+        let timeline = RemovalTimeline::new(&read_grid(input_path), &options.rules);
+        println!("{}", timeline_to_json(&timeline));
+        return;
+    }
+
+    // The animation clears the screen, so it's shown instead of the usual output
+    if let Some(pacing) = options.animation {
+        let timeline = RemovalTimeline::new(&read_grid(input_path), &options.rules);
+        animation::animate(&timeline, &pacing).expect("Unable to write to the terminal");
+        return;
+    }
+
+    println!("----- Day 4 -----");
+    println!("Input File: {}", input_path);
+
+    if let Some(input_format) = options.sparse {
+        let (accessible, removable) = solve_sparse(input_path, input_format, &options.rules);
+        println!("Part 1: {}", accessible);
+        println!("Part 2: {}", removable);
+        return;
+    }
+
+    if options.lattice != Lattice::Square {
+        let (accessible, removable) = solve_lattice(input_path, &options);
+        println!("Part 1: {}", accessible);
+        println!("Part 2: {}", removable);
+        return;
+    }
+
+    println!("Part 1: {}", solve_part_1(input_path, &options.rules));
+    println!("Part 2: {}", solve_part_2(input_path, &options.rules));
+
+    if options.show_rounds || options.show_remaining {
+        let timeline = RemovalTimeline::new(&read_grid(input_path), &options.rules);
+
+        if options.show_rounds {
+            println!();
+            println!("Removal Rounds:");
+            print!("{}", format_removals(&timeline.removals));
+
+            for (round_idx, count) in timeline.round_counts.iter().enumerate() {
+                println!("- Round {}: {} rolls", round_idx + 1, count);
+            }
+        }
+
+        if options.show_remaining {
+            println!();
+            println!("Remaining Rolls:");
+            print!("{}", timeline.remaining());
+        }
+    }
+
+    if let Some(target) = options.reach_target {
+        println!();
+
+        match roll_queries::dependency_set(&read_grid(input_path), &options.rules, target) {
+            Ok(dependencies) if dependencies.is_empty() => {
+                println!("Roll at {:?} is already accessible", target);
+            }
+            Ok(dependencies) => {
+                println!(
+                    "Roll at {:?} needs {} rolls removed first, in this order:",
+                    target,
+                    dependencies.len()
+                );
+
+                for (row_idx, col_idx) in dependencies {
+                    println!("- ({}, {})", row_idx, col_idx);
+                }
+            }
+            Err(err) => println!("{}", err),
+        }
+    }
+
+    if options.show_core {
+        let core = roll_queries::stable_core(&read_grid(input_path), &options.rules);
+
+        println!();
+        println!("Stable Core: {} rolls", core.len());
+        for (row_idx, col_idx) in core {
+            println!("- ({}, {})", row_idx, col_idx);
+        }
+    }
+
+    if options.image_path.is_some() || options.frames_dir.is_some() {
+        let timeline = RemovalTimeline::new(&read_grid(input_path), &options.rules);
+
+        if let Some(image_path) = &options.image_path {
+            export::write_image(&timeline, &options.export_style, image_path).unwrap_or_else(
+                |err| {
+                    eprintln!("Unable to write image '{}': {}", image_path.display(), err);
+                    process::exit(1);
+                },
+            );
+
+            println!("Image: {}", image_path.display());
+        }
+
+        if let Some(frames_dir) = &options.frames_dir {
+            let frame_count = export::write_frames(&timeline, &options.export_style, frames_dir)
+                .unwrap_or_else(|err| {
+                    eprintln!(
+                        "Unable to write frames to '{}': {}",
+                        frames_dir.display(),
+                        err
+                    );
+                    process::exit(1);
+                });
+
+            println!("Frames: {} images in {}", frame_count, frames_dir.display());
+        }
+    }
+
+    if let Some(edit_source) = &options.edits {
+        let mut live_grid = LiveGrid::new(&read_grid(input_path), &options.rules);
+
+        println!();
+        println!("Edits: {} accessible rolls", live_grid.accessible_count());
+
+        let result = match edit_source {
+            EditSource::File(edits_path) => {
+                let edits_file = fs::File::open(edits_path).unwrap_or_else(|err| {
+                    eprintln!(
+                        "Unable to open edits file '{}': {}",
+                        edits_path.display(),
+                        err
+                    );
+                    process::exit(1);
+                });
+
+                edits::run_edits(&mut live_grid, BufReader::new(edits_file), false)
+            }
+            EditSource::Repl => {
+                println!("Type 'help' for a list of commands");
+                edits::run_edits(&mut live_grid, io::stdin().lock(), true)
+            }
+        };
+
+        if let Err(err) = result {
+            eprintln!("{}", err);
+            process::exit(1);
+        }
+    }
+}
+
+/// Time between frames when animating, unless `--delay` is given.
+const DEFAULT_FRAME_DELAY: Duration = Duration::from_millis(250);
+
+/// Width and height of each cell in exported images, unless `--scale` is given.
+const DEFAULT_IMAGE_SCALE: usize = 4;
+
+/// Largest `--radius`, which keeps the neighbourhood to about a million cells even in 3D.
+const MAX_RADIUS: usize = 50;
+
+struct Options {
+    show_rounds: bool,
+    show_remaining: bool,
+    json: bool,
+    animation: Option<Pacing>,
+    image_path: Option<PathBuf>,
+    frames_dir: Option<PathBuf>,
+    export_style: ExportStyle,
+    lattice: Lattice,
+
+    /// Shape chosen by `--neighbourhood`, if it was given.
+    shape: Option<NeighbourhoodShape>,
+    radius: usize,
+    weights: Vec<u32>,
+    rules: Rules,
+
+    /// Stores only the roll positions, reading the input in this format.
+    sparse: Option<SparseFormat>,
+
+    /// Finds the rolls that need to be removed before this roll can be reached.
+    reach_target: Option<(usize, usize)>,
+    show_core: bool,
+
+    /// Places and removes rolls after the usual output, reading the edits from here.
+    edits: Option<EditSource>,
+}
+
+enum EditSource {
+    /// A file with one edit per line.
+    File(PathBuf),
+
+    /// Edits typed in at a prompt.
+    Repl,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum SparseFormat {
+    /// The usual `.`/`@` map.
+    Map,
+
+    /// One `row,col` pair per line.
+    Coordinates,
+}
+
+/// How the cells in the input file are laid out.
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Lattice {
+    Square,
+
+    /// Hex map in axial coordinates (see `lattice::parse_hex`).
+    Hex,
+
+    /// Square maps stacked on top of each other, separated by blank lines.
+    Cubic,
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Options {
+            show_rounds: false,
+            show_remaining: false,
+            json: false,
+            animation: None,
+            image_path: None,
+            frames_dir: None,
+            export_style: ExportStyle {
+                format: ImageFormat::Ppm,
+                colour_by: ColourBy::default(),
+                scale: DEFAULT_IMAGE_SCALE,
+            },
+            lattice: Lattice::Square,
+            shape: None,
+            radius: 1,
+            weights: Vec::new(),
+            rules: Rules::default(),
+            sparse: None,
+            reach_target: None,
+            show_core: false,
+            edits: None,
+        }
+    }
+}
+
+impl Options {
+    fn parse(args: &[String]) -> Result<Self, String> {
+        let mut options = Options::default();
+        let mut args_iter = args.iter();
+
+        while let Some(arg) = args_iter.next() {
+            match arg.as_str() {
+                "--rounds" => options.show_rounds = true,
+                "--remaining" => options.show_remaining = true,
+                "--json" => options.json = true,
+                "--animate" => {
+                    // Keep any pacing that was already chosen by --delay or --step
+                    if options.animation.is_none() {
+                        options.animation = Some(Pacing::Delay(DEFAULT_FRAME_DELAY));
+                    }
+                }
+                "--delay" => {
+                    let delay = args_iter.next().ok_or("Missing value for '--delay'")?;
+                    let delay = delay
+                        .parse::<u64>()
+                        .map_err(|_| format!("Invalid frame delay '{}'", delay))?;
+
+                    options.animation = Some(Pacing::Delay(Duration::from_millis(delay)));
+                }
+                "--step" => options.animation = Some(Pacing::Step),
+                "--image" => {
+                    let path = args_iter.next().ok_or("Missing value for '--image'")?;
+                    options.image_path = Some(PathBuf::from(path));
+                }
+                "--frames" => {
+                    let dir = args_iter.next().ok_or("Missing value for '--frames'")?;
+                    options.frames_dir = Some(PathBuf::from(dir));
+                }
+                "--format" => {
+                    let format = args_iter.next().ok_or("Missing value for '--format'")?;
+                    options.export_style.format = ImageFormat::parse(format)?;
+                }
+                "--colour" => {
+                    let colour_by = args_iter.next().ok_or("Missing value for '--colour'")?;
+                    options.export_style.colour_by = ColourBy::parse(colour_by)?;
+                }
+                "--scale" => {
+                    let scale = args_iter.next().ok_or("Missing value for '--scale'")?;
+                    let scale = scale
+                        .parse::<usize>()
+                        .ok()
+                        .filter(|scale| *scale > 0)
+                        .ok_or(format!("Invalid image scale '{}'", scale))?;
+
+                    options.export_style.scale = scale;
+                }
+                "--reach" => {
+                    let value = args_iter.next().ok_or("Missing value for '--reach'")?;
+                    let target = value
+                        .split_once(',')
+                        .and_then(|(row, col)| {
+                            Some((
+                                row.trim().parse::<usize>().ok()?,
+                                col.trim().parse::<usize>().ok()?,
+                            ))
+                        })
+                        .ok_or(format!("Invalid position '{}', expected 'row,col'", value))?;
+
+                    options.reach_target = Some(target);
+                }
+                "--core" => options.show_core = true,
+                "--edit" => {
+                    let path = args_iter.next().ok_or("Missing value for '--edit'")?;
+                    options.edits = Some(EditSource::File(PathBuf::from(path)));
+                }
+                "--repl" => options.edits = Some(EditSource::Repl),
+                "--sparse" => {
+                    // Keep the coordinates format if it was already chosen
+                    if options.sparse.is_none() {
+                        options.sparse = Some(SparseFormat::Map);
+                    }
+                }
+                "--coordinates" => options.sparse = Some(SparseFormat::Coordinates),
+                "--lattice" => {
+                    let value = args_iter.next().ok_or("Missing value for '--lattice'")?;
+
+                    options.lattice = match value.as_str() {
+                        "square" => Lattice::Square,
+                        "hex" => Lattice::Hex,
+                        "cubic" => Lattice::Cubic,
+                        _ => return Err(format!("Unknown lattice '{}'", value)),
+                    };
+                }
+                "--neighbourhood" => {
+                    let value = args_iter
+                        .next()
+                        .ok_or("Missing value for '--neighbourhood'")?;
+
+                    options.shape = Some(match value.as_str() {
+                        "von-neumann" => NeighbourhoodShape::VonNeumann,
+                        "moore" => NeighbourhoodShape::Moore,
+                        _ => return Err(format!("Unknown neighbourhood '{}'", value)),
+                    });
+                }
+                "--radius" => {
+                    let value = args_iter.next().ok_or("Missing value for '--radius'")?;
+                    options.radius = value
+                        .parse::<usize>()
+                        .ok()
+                        .filter(|radius| (1..=MAX_RADIUS).contains(radius))
+                        .ok_or(format!(
+                            "Invalid radius '{}', expected 1 to {}",
+                            value, MAX_RADIUS
+                        ))?;
+                }
+                "--weights" => {
+                    let value = args_iter.next().ok_or("Missing value for '--weights'")?;
+                    options.weights = value
+                        .split(',')
+                        .map(|weight| weight.trim().parse::<u32>())
+                        .collect::<Result<Vec<_>, _>>()
+                        .map_err(|_| format!("Invalid weights '{}'", value))?;
+                }
+                "--edges" => {
+                    let value = args_iter.next().ok_or("Missing value for '--edges'")?;
+
+                    options.rules.edges = match value.as_str() {
+                        "empty" => EdgeMode::Empty,
+                        "wall" => EdgeMode::Wall,
+                        "torus" => EdgeMode::Torus,
+                        _ => return Err(format!("Unknown edge mode '{}'", value)),
+                    };
+                }
+                "--threshold" => {
+                    let value = args_iter.next().ok_or("Missing value for '--threshold'")?;
+                    options.rules.threshold = value
+                        .parse::<u32>()
+                        .map_err(|_| format!("Invalid threshold '{}'", value))?;
+                }
+                _ => return Err(format!("Unknown option '{}'", arg)),
+            }
+        }
+
+        // The neighbourhood is built once all of its options have been seen
+        options.rules.neighbourhood = Neighbourhood::new(options.shape(), options.radius)
+            .with_distance_weights(&options.weights);
+
+        let square_only = options.json
+            || options.animation.is_some()
+            || options.show_rounds
+            || options.show_remaining
+            || options.image_path.is_some()
+            || options.frames_dir.is_some()
+            || options.reach_target.is_some()
+            || options.show_core
+            || options.edits.is_some();
+
+        if options.lattice != Lattice::Square && square_only {
+            return Err(
+                "Timelines, animations, images, queries and edits are only available for square lattices"
+                    .to_string(),
+            );
+        }
+
+        if options.lattice == Lattice::Hex && options.shape.is_some() {
+            return Err(
+                "Hex lattices use every cell within the radius, so '--neighbourhood' can't be used"
+                    .to_string(),
+            );
+        }
+
+        if options.sparse.is_some() && (options.lattice != Lattice::Square || square_only) {
+            return Err(
+                "Sparse maps only give the part 1 and 2 answers, for square lattices".to_string(),
+            );
+        }
+
+        Ok(options)
+    }
+
+    /// Gets the neighbourhood shape, which is the 8 surrounding cells unless it was chosen.
+    fn shape(&self) -> NeighbourhoodShape {
+        self.shape.unwrap_or(NeighbourhoodShape::Moore)
+    }
+}
+
+/// Reads the input grid, exiting if it can't be parsed.
+fn read_grid(path: &str) -> Grid<CellType> {
+    let input = fs::read_to_string(path).expect("Unable to open input file");
+
+    Grid::parse(&input, CellType::try_from).unwrap_or_else(|err| {
+        eprintln!("{}", err);
+        process::exit(1);
+    })
+}
+
+/*
+ * While working on part 1, I guessed that part 2 would involve finding all accessible paper rolls.
+ * I was supposed to use GridCell in part 1, but then I got hit by the borrow checker haha.
+ *
+ * Eventually, I realised I could get around it by using a flattened representation of the grid.
+ * That representation has since become the shared Grid type, and the removal logic has moved into
+ * the paper_rolls module, which uses a worklist instead of rescanning the grid every round.
+ *
+ * With the puzzle's rules, both parts now use a bit-packed grid instead, which counts the
+ * neighbours of 64 cells at a time. The worklist is still used for any other rules.
+ */
+
+/// Finds number of paper rolls that have fewer than 4 adjacent paper rolls in the 8 surrounding cells.
+///
+/// **Answer**: `1518` (with the default rules)
+fn solve_part_1(input_path: &str, rules: &Rules) -> usize {
+    let grid = read_grid(input_path);
+
+    if uses_bit_grid(rules) {
+        let rolls = BitGrid::from_grid(&grid, |cell| *cell == CellType::PaperRoll);
+        return rolls.with_fewer_neighbours(rules.threshold).count_ones();
+    }
+
+    count_accessible(&grid, rules)
+}
+
+/// Incrementally finds all accessible paper rolls.
+///
+/// **Answer**: `8665` (with the default rules)
+fn solve_part_2(input_path: &str, rules: &Rules) -> usize {
+    let grid = read_grid(input_path);
+
+    if uses_bit_grid(rules) {
+        let rolls = BitGrid::from_grid(&grid, |cell| *cell == CellType::PaperRoll);
+        return rolls.peel(rules.threshold).iter().sum();
+    }
+
+    removal_rounds(&grid, rules)
+        .iter()
+        .map(|round| round.len())
+        .sum()
+}
+
+/// Solves both parts using only the positions of the rolls, so that huge but mostly empty maps
+/// don't need any memory or time for the empty cells.
+fn solve_sparse(input_path: &str, input_format: SparseFormat, rules: &Rules) -> (usize, usize) {
+    let input = fs::read_to_string(input_path).expect("Unable to open input file");
+
+    let rolls = match input_format {
+        SparseFormat::Map => SparseRolls::parse_map(&input).map_err(|err| err.to_string()),
+        SparseFormat::Coordinates => {
+            SparseRolls::parse_coordinates(&input).map_err(|err| err.to_string())
+        }
+    }
+    .unwrap_or_else(|err| {
+        eprintln!("{}", err);
+        process::exit(1);
+    });
+
+    let accessible = rolls.count_accessible(rules);
+    let removable = rolls
+        .removal_rounds(rules)
+        .iter()
+        .map(|round| round.len())
+        .sum();
+
+    (accessible, removable)
+}
+
+/// Checks whether the bit-packed grid can be used, which only counts the 8 surrounding cells (all
+/// weighted the same) and treats cells beyond the edges as empty.
+fn uses_bit_grid(rules: &Rules) -> bool {
+    rules.neighbourhood == Neighbourhood::moore() && rules.edges == EdgeMode::Empty
+}
+
+/// Solves both parts on a hex or cubic lattice, returning the number of rolls that are accessible
+/// straight away and the number that can be removed.
+///
+/// Hex lattices always use every cell within the radius, so they don't have a neighbourhood shape.
+fn solve_lattice(input_path: &str, options: &Options) -> (usize, usize) {
+    let input = fs::read_to_string(input_path).expect("Unable to open input file");
+    let threshold = options.rules.threshold;
+
+    match options.lattice {
+        Lattice::Square => unreachable!("Square lattices are solved by solve_part_1/2"),
+        Lattice::Hex => {
+            let grid = lattice::parse_hex(&input, CellType::try_from).unwrap_or_else(|err| {
+                eprintln!("{}", err);
+                process::exit(1);
+            });
+
+            let hex = HexLattice::new(
+                grid.rows(),
+                grid.cols(),
+                options.radius,
+                &options.weights,
+                options.rules.edges,
+            );
+
+            solve_topology(&hex, grid.cells(), threshold)
+        }
+        Lattice::Cubic => {
+            let layers = lattice::parse_layers(&input, CellType::try_from).unwrap_or_else(|err| {
+                eprintln!("{}", err);
+                process::exit(1);
+            });
+
+            let (rows, cols) = layers
+                .first()
+                .map_or((0, 0), |layer| (layer.rows(), layer.cols()));
+            let cells = layers
+                .iter()
+                .flat_map(|layer| layer.cells().iter().copied())
+                .collect::<Vec<_>>();
+
+            let cubic = CubicLattice::new(
+                layers.len(),
+                rows,
+                cols,
+                options.shape(),
+                options.radius,
+                &options.weights,
+                options.rules.edges,
+            );
+
+            solve_topology(&cubic, &cells, threshold)
+        }
+    }
+}
+
+/// Counts the rolls that are accessible straight away, then the rolls that can be removed.
+fn solve_topology(topology: &impl Topology, cells: &[CellType], threshold: u32) -> (usize, usize) {
+    let accessible = count_accessible_in(topology, cells, threshold);
+    let removable = removal_rounds_in(topology, cells, threshold)
+        .iter()
+        .map(|round| round.len())
+        .sum();
+
+    (accessible, removable)
+}
+
+/// Formats the round that each roll was removed in, lined up in columns.
+///
+/// Empty cells are shown as `.`, and rolls that are never removed are shown as `@`.
+fn format_removals(removals: &Grid<Removal>) -> String {
+    let format_removal = |removal: &Removal| match removal {
+        Removal::NoRoll => ".".to_string(),
+        Removal::Round(round) => round.to_string(),
+        Removal::Never => "@".to_string(),
+    };
+
+    let width = removals
+        .iter()
+        .map(|(_, removal)| format_removal(removal).len())
+        .max()
+        .unwrap_or(1);
+
+    let mut output = String::new();
+
+    for row in removals.iter_rows() {
+        let cells = row
+            .iter()
+            .map(|removal| format!("{:>width$}", format_removal(removal), width = width))
+            .collect::<Vec<_>>();
+
+        output.push_str(&cells.join(" "));
+        output.push('\n');
+    }
+
+    output
+}
+
+/// Formats the timeline as JSON.
+///
+/// Each cell in `removal_rounds` is `null` if there's no roll, the round the roll was removed in,
+/// or `"never"` if it's never removed. `remaining` is the stable configuration as a `.`/`@` map.
+fn timeline_to_json(timeline: &RemovalTimeline) -> String {
+    let removal_rows = timeline
+        .removals
+        .iter_rows()
+        .map(|row| {
+            let cells = row
+                .iter()
+                .map(|removal| match removal {
+                    Removal::NoRoll => "null".to_string(),
+                    Removal::Round(round) => round.to_string(),
+                    Removal::Never => "\"never\"".to_string(),
+                })
+                .collect::<Vec<_>>();
+
+            format!("[{}]", cells.join(", "))
+        })
+        .collect::<Vec<_>>();
+
+    let remaining_rows = timeline
+        .remaining()
+        .iter_rows()
+        .map(|row| {
+            let row_str = row.iter().map(|cell| cell.to_string()).collect::<String>();
+            format!("\"{}\"", row_str)
+        })
+        .collect::<Vec<_>>();
+
+    format!(
+        "{{\"rows\": {}, \"cols\": {}, \"total_removed\": {}, \"round_counts\": {:?}, \"removal_rounds\": [{}], \"remaining\": [{}]}}",
+        timeline.removals.rows(),
+        timeline.removals.cols(),
+        timeline.total_removed(),
+        timeline.round_counts,
+        removal_rows.join(", "),
+        remaining_rows.join(", ")
+    )
+}
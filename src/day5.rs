@@ -55,6 +55,8 @@ impl Database {
     }
 }
 
+// NOTE: Lines that can't be read are skipped, rather than ending the input
+#[allow(clippy::lines_filter_map_ok)]
 fn read_lines(path: &str) -> impl Iterator<Item = String> {
     let file = File::open(path).expect("Unable to open input file");
     let reader = BufReader::new(file);
@@ -68,7 +70,7 @@ fn parse_input(input_path: &str) -> (Database, Vec<u64>) {
     // Parse ID ranges up until 1st empty line
     let mut database = Database::default();
 
-    while let Some(id_range_line) = input_iter.next() {
+    for id_range_line in input_iter.by_ref() {
         if let Some((start_str, end_str)) = id_range_line.trim().split_once("-") {
             let start = start_str.parse::<u64>().unwrap();
             let end = end_str.parse::<u64>().unwrap();
@@ -21,6 +21,8 @@ fn main() {
     println!("Part 2: {}", solve_part_2(input_path));
 }
 
+// NOTE: Lines that can't be read are skipped, rather than ending the input
+#[allow(clippy::lines_filter_map_ok)]
 fn read_lines(path: &str) -> impl Iterator<Item = String> {
     let file = File::open(path).expect("Unable to open input file");
     let reader = BufReader::new(file);
@@ -32,16 +34,16 @@ fn read_lines(path: &str) -> impl Iterator<Item = String> {
 ///
 /// **Answer**: `4951502530386`
 fn solve_part_1(input_path: &str) -> u64 {
-    let mut input_lines = read_lines(input_path);
+    let input_lines = read_lines(input_path);
 
     // Parse numbers that need to be added/multiplied
     let mut number_lines: Vec<Vec<u64>> = Vec::new();
     let mut grand_total = 0;
 
-    while let Some(line) = input_lines.next() {
-        let values = line.trim().split_whitespace().collect::<Vec<_>>();
+    for line in input_lines {
+        let values = line.split_whitespace().collect::<Vec<_>>();
 
-        if let Ok(_) = values.first().unwrap().parse::<u64>() {
+        if values.first().unwrap().parse::<u64>().is_ok() {
             // First value in the line is a number, so add to list of number lines
             let number_line = values
                 .into_iter()
@@ -53,9 +55,9 @@ fn solve_part_1(input_path: &str) -> u64 {
             // First value is an operator, so start calculating grand total
             for (problem_idx, &op) in values.iter().enumerate() {
                 let problem_nums = number_lines.iter().map(|l| l[problem_idx]);
-                let intermediate_total = match op {
+                let intermediate_total: u64 = match op {
                     "+" => problem_nums.sum(),
-                    "*" => problem_nums.fold(1, |acc, x| acc * x),
+                    "*" => problem_nums.product(),
                     _ => panic!("Unexpected operator '{}'", op),
                 };
 
@@ -102,19 +104,15 @@ impl Operator {
 ///
 /// **Answer**: `8486156119946`
 fn solve_part_2(input_path: &str) -> u64 {
-    let mut input_lines = read_lines(input_path);
+    let input_lines = read_lines(input_path);
 
     // Parse individual characters
     let mut digit_lines: Vec<Vec<Option<u8>>> = Vec::new();
     let mut operators: Vec<Operator> = Vec::with_capacity(0);
 
-    while let Some(line) = input_lines.next() {
+    for line in input_lines {
         // Determine what type of line to parse based on 1st non-empty character
-        let first_char = line
-            .chars()
-            .skip_while(|c| c.is_whitespace())
-            .next()
-            .unwrap();
+        let first_char = line.chars().find(|c| !c.is_whitespace()).unwrap();
 
         if first_char.is_ascii_digit() {
             // Parse as a digit line
@@ -158,8 +156,7 @@ fn solve_part_2(input_path: &str) -> u64 {
         // Attempt to combine the digits at this index
         let problem_number = digit_lines
             .iter()
-            .map(|l| l[digit_idx])
-            .filter_map(|d| d)
+            .filter_map(|l| l[digit_idx])
             .map(|d| d as u64)
             .reduce(|acc, d| (acc * 10) + d);
 
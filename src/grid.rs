@@ -0,0 +1,523 @@
+//! Generic 2D grid, for puzzles where the input is a map of characters (e.g. day 4).
+
+use std::{
+    error::Error,
+    fmt,
+    ops::{Index, IndexMut},
+};
+
+/// Offsets to the 4 orthogonal neighbours (up, left, right, down).
+const ORTHOGONAL_OFFSETS: [(isize, isize); 4] = [(-1, 0), (0, -1), (0, 1), (1, 0)];
+
+/// Offsets to the 8 surrounding neighbours, row by row.
+const SURROUNDING_OFFSETS: [(isize, isize); 8] = [
+    (-1, -1),
+    (-1, 0),
+    (-1, 1),
+    (0, -1),
+    (0, 1),
+    (1, -1),
+    (1, 0),
+    (1, 1),
+];
+
+/// Problem found while parsing a grid, located by its line and column (starting at 1).
+#[derive(Debug, PartialEq, Eq)]
+pub enum GridError<E> {
+    /// A character couldn't be converted into a cell.
+    InvalidCell {
+        line_number: usize,
+        column: usize,
+        error: E,
+    },
+
+    /// A row has a different length to the first row.
+    RaggedRow {
+        line_number: usize,
+        expected: usize,
+        found: usize,
+    },
+}
+
+impl<E: fmt::Display> fmt::Display for GridError<E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            GridError::InvalidCell {
+                line_number,
+                column,
+                error,
+            } => write!(f, "Line {}, column {}: {}", line_number, column, error),
+            GridError::RaggedRow {
+                line_number,
+                expected,
+                found,
+            } => write!(
+                f,
+                "Line {}: Expected {} cells, but found {}",
+                line_number, expected, found
+            ),
+        }
+    }
+}
+
+impl<E: fmt::Debug + fmt::Display> Error for GridError<E> {}
+
+/// Problem with the dimensions of the data used to build a grid.
+#[derive(Debug, PartialEq, Eq)]
+pub enum ShapeError {
+    /// A row has a different length to the first row (rows start at 0).
+    RaggedRow {
+        row: usize,
+        expected: usize,
+        found: usize,
+    },
+
+    /// The number of cells doesn't match the dimensions.
+    LengthMismatch {
+        rows: usize,
+        cols: usize,
+        len: usize,
+    },
+}
+
+impl fmt::Display for ShapeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ShapeError::RaggedRow {
+                row,
+                expected,
+                found,
+            } => write!(
+                f,
+                "Row {} has {} cells, but the first row has {}",
+                row, found, expected
+            ),
+            ShapeError::LengthMismatch { rows, cols, len } => write!(
+                f,
+                "A {}x{} grid needs {} cells, but there are {}",
+                rows,
+                cols,
+                rows.saturating_mul(*cols),
+                len
+            ),
+        }
+    }
+}
+
+impl Error for ShapeError {}
+
+/// 2D grid, stored as a flat vector in row-major order.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Grid<T> {
+    data: Vec<T>,
+    rows: usize,
+    cols: usize,
+}
+
+impl<T> Grid<T> {
+    /// Creates a grid where every cell has the same value.
+    pub fn new(rows: usize, cols: usize, value: T) -> Self
+    where
+        T: Clone,
+    {
+        Grid {
+            data: vec![value; rows * cols],
+            rows,
+            cols,
+        }
+    }
+
+    /// Creates a grid from cells in row-major order, checking that they fit the dimensions.
+    pub fn from_vec(rows: usize, cols: usize, data: Vec<T>) -> Result<Self, ShapeError> {
+        if rows.checked_mul(cols) != Some(data.len()) {
+            return Err(ShapeError::LengthMismatch {
+                rows,
+                cols,
+                len: data.len(),
+            });
+        }
+
+        Ok(Grid { data, rows, cols })
+    }
+
+    /// Parses a grid from a map of characters, where each non-empty line is a row.
+    ///
+    /// Whitespace around each line is ignored (including `\r` from Windows line endings).
+    pub fn parse<E>(
+        input: &str,
+        mut parse_cell: impl FnMut(char) -> Result<T, E>,
+    ) -> Result<Self, GridError<E>> {
+        let mut data = Vec::new();
+        let mut rows = 0;
+        let mut cols = 0;
+
+        for (line_idx, line) in input.lines().enumerate() {
+            let line_number = line_idx + 1;
+            let trimmed_line = line.trim();
+
+            if trimmed_line.is_empty() {
+                continue;
+            }
+
+            let leading_chars = line.chars().count() - line.trim_start().chars().count();
+            let row_start = data.len();
+
+            for (char_idx, c) in trimmed_line.chars().enumerate() {
+                let cell = parse_cell(c).map_err(|error| GridError::InvalidCell {
+                    line_number,
+                    column: leading_chars + char_idx + 1,
+                    error,
+                })?;
+
+                data.push(cell);
+            }
+
+            let row_len = data.len() - row_start;
+            if rows == 0 {
+                cols = row_len;
+            } else if row_len != cols {
+                return Err(GridError::RaggedRow {
+                    line_number,
+                    expected: cols,
+                    found: row_len,
+                });
+            }
+
+            rows += 1;
+        }
+
+        Ok(Grid { data, rows, cols })
+    }
+
+    pub const fn rows(&self) -> usize {
+        self.rows
+    }
+
+    pub const fn cols(&self) -> usize {
+        self.cols
+    }
+
+    const fn calculate_idx(&self, row: usize, col: usize) -> usize {
+        (self.cols * row) + col
+    }
+
+    const fn contains(&self, row: usize, col: usize) -> bool {
+        row < self.rows && col < self.cols
+    }
+
+    pub fn get(&self, row: usize, col: usize) -> Option<&T> {
+        if self.contains(row, col) {
+            Some(&self.data[self.calculate_idx(row, col)])
+        } else {
+            None
+        }
+    }
+
+    pub fn get_mut(&mut self, row: usize, col: usize) -> Option<&mut T> {
+        if self.contains(row, col) {
+            let idx = self.calculate_idx(row, col);
+            Some(&mut self.data[idx])
+        } else {
+            None
+        }
+    }
+
+    /// Gets every cell in row-major order.
+    pub fn cells(&self) -> &[T] {
+        &self.data
+    }
+
+    /// Gets the cells in a row, from left to right.
+    pub fn row(&self, row: usize) -> Option<&[T]> {
+        if row < self.rows {
+            let start = self.calculate_idx(row, 0);
+            Some(&self.data[start..(start + self.cols)])
+        } else {
+            None
+        }
+    }
+
+    /// Gets the cells in a column, from top to bottom.
+    pub fn column(&self, col: usize) -> Option<impl Iterator<Item = &T>> {
+        if col < self.cols {
+            Some(self.data.iter().skip(col).step_by(self.cols))
+        } else {
+            None
+        }
+    }
+
+    /// Iterates over each row, from top to bottom.
+    pub fn iter_rows(&self) -> impl Iterator<Item = &[T]> {
+        // NOTE: Not using chunks(), as that doesn't give any rows when there are 0 columns
+        (0..self.rows).map(|row| {
+            let start = self.calculate_idx(row, 0);
+            &self.data[start..(start + self.cols)]
+        })
+    }
+
+    /// Iterates over each cell in row-major order, along with its position.
+    pub fn iter(&self) -> impl Iterator<Item = ((usize, usize), &T)> {
+        self.positions().zip(self.data.iter())
+    }
+
+    /// Iterates over each position in row-major order.
+    pub fn positions(&self) -> impl Iterator<Item = (usize, usize)> + use<T> {
+        let cols = self.cols;
+        (0..self.rows).flat_map(move |row| (0..cols).map(move |col| (row, col)))
+    }
+
+    /// Iterates over the positions of the (up to) 4 orthogonal neighbours that are in the grid.
+    pub fn neighbours4(
+        &self,
+        row: usize,
+        col: usize,
+    ) -> impl Iterator<Item = (usize, usize)> + use<T> {
+        self.offset_positions(row, col, &ORTHOGONAL_OFFSETS)
+    }
+
+    /// Iterates over the positions of the (up to) 8 surrounding neighbours that are in the grid.
+    pub fn neighbours8(
+        &self,
+        row: usize,
+        col: usize,
+    ) -> impl Iterator<Item = (usize, usize)> + use<T> {
+        self.offset_positions(row, col, &SURROUNDING_OFFSETS)
+    }
+
+    /// Gets the position at an offset from another position, if it's in the grid.
+    pub fn offset_position(
+        &self,
+        row: usize,
+        col: usize,
+        (row_offset, col_offset): (isize, isize),
+    ) -> Option<(usize, usize)> {
+        let offset_row = row.checked_add_signed(row_offset)?;
+        let offset_col = col.checked_add_signed(col_offset)?;
+
+        self.contains(offset_row, offset_col)
+            .then_some((offset_row, offset_col))
+    }
+
+    /// Gets the position at an offset from another position, wrapping around the edges of the grid.
+    ///
+    /// The starting position must be in the grid.
+    pub fn wrapping_offset_position(
+        &self,
+        row: usize,
+        col: usize,
+        (row_offset, col_offset): (isize, isize),
+    ) -> (usize, usize) {
+        let offset_row = (row as isize + row_offset).rem_euclid(self.rows as isize);
+        let offset_col = (col as isize + col_offset).rem_euclid(self.cols as isize);
+
+        (offset_row as usize, offset_col as usize)
+    }
+
+    fn offset_positions(
+        &self,
+        row: usize,
+        col: usize,
+        offsets: &'static [(isize, isize)],
+    ) -> impl Iterator<Item = (usize, usize)> + use<T> {
+        let (rows, cols) = (self.rows, self.cols);
+
+        offsets.iter().filter_map(move |&(row_offset, col_offset)| {
+            let neighbour_row = row.checked_add_signed(row_offset)?;
+            let neighbour_col = col.checked_add_signed(col_offset)?;
+
+            (neighbour_row < rows && neighbour_col < cols).then_some((neighbour_row, neighbour_col))
+        })
+    }
+
+    /// Creates a grid of the same size, by converting each cell.
+    pub fn map<U>(&self, f: impl FnMut(&T) -> U) -> Grid<U> {
+        Grid {
+            data: self.data.iter().map(f).collect(),
+            rows: self.rows,
+            cols: self.cols,
+        }
+    }
+}
+
+impl<T> TryFrom<Vec<Vec<T>>> for Grid<T> {
+    type Error = ShapeError;
+
+    /// Flattens a list of rows, rejecting any rows that are a different length to the first.
+    fn try_from(value: Vec<Vec<T>>) -> Result<Self, Self::Error> {
+        let rows = value.len();
+        let cols = value.first().map_or(0, |row| row.len());
+
+        if let Some((row, found)) = value
+            .iter()
+            .map(|row| row.len())
+            .enumerate()
+            .find(|(_, len)| *len != cols)
+        {
+            return Err(ShapeError::RaggedRow {
+                row,
+                expected: cols,
+                found,
+            });
+        }
+
+        Ok(Grid {
+            data: value.into_iter().flatten().collect(),
+            rows,
+            cols,
+        })
+    }
+}
+
+impl<T> Index<(usize, usize)> for Grid<T> {
+    type Output = T;
+
+    fn index(&self, (row, col): (usize, usize)) -> &Self::Output {
+        self.get(row, col).unwrap_or_else(|| {
+            panic!(
+                "Position ({}, {}) is outside of a {}x{} grid",
+                row, col, self.rows, self.cols
+            )
+        })
+    }
+}
+
+impl<T> IndexMut<(usize, usize)> for Grid<T> {
+    fn index_mut(&mut self, (row, col): (usize, usize)) -> &mut Self::Output {
+        let (rows, cols) = (self.rows, self.cols);
+        self.get_mut(row, col).unwrap_or_else(|| {
+            panic!(
+                "Position ({}, {}) is outside of a {}x{} grid",
+                row, col, rows, cols
+            )
+        })
+    }
+}
+
+impl<T: fmt::Display> fmt::Display for Grid<T> {
+    /// Renders each row on its own line, with the cells next to each other.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for row in self.iter_rows() {
+            for cell in row {
+                write!(f, "{}", cell)?;
+            }
+
+            writeln!(f)?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::paper_rolls::CellType;
+
+    /// 2x5 grid where each cell is `(row * 10) + col`.
+    fn wide_grid() -> Grid<usize> {
+        Grid::try_from(vec![vec![0, 1, 2, 3, 4], vec![10, 11, 12, 13, 14]]).unwrap()
+    }
+
+    /// 5x2 grid where each cell is `(row * 10) + col`.
+    fn tall_grid() -> Grid<usize> {
+        Grid::from_vec(5, 2, vec![0, 1, 10, 11, 20, 21, 30, 31, 40, 41]).unwrap()
+    }
+
+    #[test]
+    fn indexes_rectangular_grids() {
+        for grid in [wide_grid(), tall_grid()] {
+            for ((row, col), cell) in grid.iter() {
+                assert_eq!(*cell, (row * 10) + col);
+                assert_eq!(grid[(row, col)], (row * 10) + col);
+            }
+
+            assert_eq!(grid.get(grid.rows(), 0), None);
+            assert_eq!(grid.get(0, grid.cols()), None);
+        }
+
+        let wide = wide_grid();
+        assert_eq!((wide.rows(), wide.cols()), (2, 5));
+        assert_eq!(wide.get(1, 4), Some(&14));
+        assert_eq!(wide.get(4, 1), None);
+
+        let tall = tall_grid();
+        assert_eq!((tall.rows(), tall.cols()), (5, 2));
+        assert_eq!(tall.get(4, 1), Some(&41));
+        assert_eq!(tall.get(1, 4), None);
+    }
+
+    #[test]
+    #[should_panic(expected = "Position (0, 2) is outside of a 5x2 grid")]
+    fn index_outside_tall_grid_panics() {
+        let _ = tall_grid()[(0, 2)];
+    }
+
+    #[test]
+    fn gets_rows_and_columns_of_rectangular_grids() {
+        let wide = wide_grid();
+        assert_eq!(wide.row(1), Some([10, 11, 12, 13, 14].as_slice()));
+        assert_eq!(wide.row(2), None);
+        assert_eq!(
+            wide.column(3).unwrap().copied().collect::<Vec<_>>(),
+            [3, 13]
+        );
+        assert!(wide.column(5).is_none());
+        assert_eq!(
+            wide.iter_rows().collect::<Vec<_>>(),
+            [[0, 1, 2, 3, 4].as_slice(), &[10, 11, 12, 13, 14]]
+        );
+
+        let tall = tall_grid();
+        assert_eq!(tall.row(4), Some([40, 41].as_slice()));
+        assert_eq!(tall.row(5), None);
+        assert_eq!(
+            tall.column(1).unwrap().copied().collect::<Vec<_>>(),
+            [1, 11, 21, 31, 41]
+        );
+        assert!(tall.column(2).is_none());
+        assert_eq!(tall.iter_rows().count(), 5);
+        assert!(tall.iter_rows().all(|row| row.len() == 2));
+    }
+
+    #[test]
+    fn rejects_ragged_rows() {
+        assert_eq!(
+            Grid::try_from(vec![vec![1, 2, 3], vec![4, 5, 6], vec![7, 8]]),
+            Err(ShapeError::RaggedRow {
+                row: 2,
+                expected: 3,
+                found: 2
+            })
+        );
+
+        assert_eq!(
+            Grid::parse("@@.\n\n@.@.\n", CellType::try_from),
+            Err(GridError::RaggedRow {
+                line_number: 3,
+                expected: 3,
+                found: 4
+            })
+        );
+    }
+
+    #[test]
+    fn rejects_mismatched_lengths() {
+        assert_eq!(
+            Grid::from_vec(2, 5, vec![0; 9]),
+            Err(ShapeError::LengthMismatch {
+                rows: 2,
+                cols: 5,
+                len: 9
+            })
+        );
+
+        assert_eq!(
+            Grid::from_vec(usize::MAX, 2, vec![0; 2]),
+            Err(ShapeError::LengthMismatch {
+                rows: usize::MAX,
+                cols: 2,
+                len: 2
+            })
+        );
+    }
+}
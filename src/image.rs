@@ -0,0 +1,198 @@
+//! Minimal RGB images, for exporting grids to image files without any dependencies.
+//!
+//! PPM is always available. PNG needs the `png` feature, and is written with uncompressed
+//! (stored) deflate blocks, so the files are larger than they'd be from an image editor.
+
+use std::io::{self, Write};
+
+use crate::grid::Grid;
+
+/// Red, green and blue values of a pixel.
+pub type Rgb = [u8; 3];
+
+/// RGB image, stored as a flat vector of pixels in row-major order.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Image {
+    width: usize,
+    height: usize,
+    pixels: Vec<Rgb>,
+}
+
+impl Image {
+    /// Draws each grid cell as a `scale` x `scale` square, coloured by `colour`.
+    pub fn from_grid<T>(grid: &Grid<T>, scale: usize, mut colour: impl FnMut(&T) -> Rgb) -> Self {
+        let width = grid.cols() * scale;
+        let height = grid.rows() * scale;
+        let mut pixels = Vec::with_capacity(width * height);
+
+        for row in grid.iter_rows() {
+            let row_colours = row.iter().map(&mut colour).collect::<Vec<_>>();
+
+            for _ in 0..scale {
+                for row_colour in row_colours.iter() {
+                    pixels.extend(std::iter::repeat_n(*row_colour, scale));
+                }
+            }
+        }
+
+        Image {
+            width,
+            height,
+            pixels,
+        }
+    }
+
+    pub const fn width(&self) -> usize {
+        self.width
+    }
+
+    pub const fn height(&self) -> usize {
+        self.height
+    }
+
+    /// Writes the image in binary PPM (P6) format.
+    pub fn write_ppm(&self, mut writer: impl Write) -> io::Result<()> {
+        write!(writer, "P6\n{} {}\n255\n", self.width, self.height)?;
+        writer.write_all(self.pixels.as_flattened())
+    }
+
+    /// Writes the image in PNG format, as 8-bit RGB without any compression.
+    ///
+    /// PNG doesn't allow empty images, so this fails if the width or height is 0.
+    #[cfg(feature = "png")]
+    pub fn write_png(&self, mut writer: impl Write) -> io::Result<()> {
+        let (width, height) = match (u32::try_from(self.width), u32::try_from(self.height)) {
+            (Ok(width), Ok(height)) if width > 0 && height > 0 => (width, height),
+            _ => {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    format!("PNG images can't be {}x{} pixels", self.width, self.height),
+                ));
+            }
+        };
+
+        writer.write_all(&png::SIGNATURE)?;
+
+        let mut header = Vec::with_capacity(13);
+        header.extend_from_slice(&width.to_be_bytes());
+        header.extend_from_slice(&height.to_be_bytes());
+        // Bit depth 8, RGB colour, deflate compression, no filtering, no interlacing
+        header.extend_from_slice(&[8, 2, 0, 0, 0]);
+        png::write_chunk(&mut writer, b"IHDR", &header)?;
+
+        // Each scanline starts with its filter type, which is always 0 (none)
+        let mut scanlines = Vec::with_capacity(self.height * (1 + (self.width * 3)));
+        for row in self.pixels.chunks(self.width) {
+            scanlines.push(0);
+            scanlines.extend_from_slice(row.as_flattened());
+        }
+
+        png::write_chunk(&mut writer, b"IDAT", &png::zlib_stored(&scanlines))?;
+        png::write_chunk(&mut writer, b"IEND", &[])
+    }
+}
+
+#[cfg(feature = "png")]
+mod png {
+    use std::io::{self, Write};
+
+    pub const SIGNATURE: [u8; 8] = [0x89, b'P', b'N', b'G', b'\r', b'\n', 0x1a, b'\n'];
+
+    /// Largest amount of data that fits in a single stored deflate block.
+    const MAX_STORED_BLOCK_LEN: usize = u16::MAX as usize;
+
+    /// CRC-32 lookup table, for the reversed polynomial used by PNG (and zip, gzip, etc.).
+    const CRC_TABLE: [u32; 256] = {
+        let mut table = [0; 256];
+        let mut n = 0;
+
+        while n < 256 {
+            let mut crc = n as u32;
+            let mut bit = 0;
+
+            while bit < 8 {
+                crc = if crc & 1 == 1 {
+                    0xedb8_8320 ^ (crc >> 1)
+                } else {
+                    crc >> 1
+                };
+                bit += 1;
+            }
+
+            table[n] = crc;
+            n += 1;
+        }
+
+        table
+    };
+
+    /// Writes a chunk, i.e. its length, type, data and CRC (of the type and data).
+    pub fn write_chunk(
+        writer: &mut impl Write,
+        chunk_type: &[u8; 4],
+        data: &[u8],
+    ) -> io::Result<()> {
+        let len = u32::try_from(data.len())
+            .map_err(|_| io::Error::new(io::ErrorKind::InvalidInput, "PNG chunk is too large"))?;
+
+        writer.write_all(&len.to_be_bytes())?;
+        writer.write_all(chunk_type)?;
+        writer.write_all(data)?;
+        writer.write_all(&crc32(&[chunk_type, data]).to_be_bytes())
+    }
+
+    fn crc32(parts: &[&[u8]]) -> u32 {
+        let mut crc = u32::MAX;
+
+        for byte in parts.iter().flat_map(|part| part.iter()) {
+            crc = CRC_TABLE[((crc ^ *byte as u32) & 0xff) as usize] ^ (crc >> 8);
+        }
+
+        !crc
+    }
+
+    fn adler32(data: &[u8]) -> u32 {
+        const MOD_ADLER: u32 = 65521;
+
+        // NOTE: 5552 is the most bytes that can be summed before the u32s could overflow
+        let (mut a, mut b) = (1, 0);
+        for chunk in data.chunks(5552) {
+            for byte in chunk {
+                a += *byte as u32;
+                b += a;
+            }
+
+            a %= MOD_ADLER;
+            b %= MOD_ADLER;
+        }
+
+        (b << 16) | a
+    }
+
+    /// Wraps data in a zlib stream, using stored (uncompressed) deflate blocks.
+    pub fn zlib_stored(data: &[u8]) -> Vec<u8> {
+        let block_count = data.len().div_ceil(MAX_STORED_BLOCK_LEN).max(1);
+        let mut stream = Vec::with_capacity(2 + data.len() + (block_count * 5) + 4);
+
+        // Deflate with a 32K window, and the check bits that make the header a multiple of 31
+        stream.extend_from_slice(&[0x78, 0x01]);
+
+        let mut blocks = data.chunks(MAX_STORED_BLOCK_LEN).peekable();
+        if blocks.peek().is_none() {
+            stream.extend_from_slice(&[1, 0x00, 0x00, 0xff, 0xff]);
+        }
+
+        while let Some(block) = blocks.next() {
+            let is_final = blocks.peek().is_none();
+            let len = block.len() as u16;
+
+            stream.push(is_final as u8);
+            stream.extend_from_slice(&len.to_le_bytes());
+            stream.extend_from_slice(&(!len).to_le_bytes());
+            stream.extend_from_slice(block);
+        }
+
+        stream.extend_from_slice(&adler32(data).to_be_bytes());
+        stream
+    }
+}
@@ -0,0 +1,366 @@
+//! Layouts of cells, which decide which cells are next to each other (used by day 4).
+//!
+//! Cells are numbered from 0 in row-major order (layer by layer for 3D lattices), so that the
+//! peeling logic in `paper_rolls` can work on a flat list of cells for any layout.
+
+use std::{error::Error, fmt};
+
+use crate::{
+    grid::{Grid, GridError},
+    paper_rolls::{EdgeMode, Neighbourhood, NeighbourhoodShape, distance_weight},
+};
+
+/// Layout of the cells, which gives the neighbours of each cell.
+pub trait Topology {
+    /// Number of cells, which are numbered from 0.
+    fn cell_count(&self) -> usize;
+
+    /// Iterates over the neighbours of a cell, along with their weights.
+    ///
+    /// Neighbours outside the layout are `None` when they're walls, and skipped otherwise.
+    /// Weights must be symmetric, i.e. if `b` is a neighbour of `a` with weight `w`, then `a` is a
+    /// neighbour of `b` with weight `w`.
+    fn neighbours(&self, idx: usize) -> impl Iterator<Item = (Option<usize>, u32)> + '_;
+}
+
+/// Finds the neighbours of a cell in a row-major layout with `D` dimensions, from a list of offsets.
+fn offset_neighbours<const D: usize>(
+    dims: [usize; D],
+    idx: usize,
+    offsets: &[([isize; D], u32)],
+    edges: EdgeMode,
+) -> impl Iterator<Item = (Option<usize>, u32)> + '_ {
+    // Convert to coordinates once, rather than for every neighbour
+    let mut coords = [0; D];
+    let mut remaining = idx;
+    for (coord, size) in coords.iter_mut().zip(dims).rev() {
+        *coord = remaining % size;
+        remaining /= size;
+    }
+
+    offsets.iter().filter_map(move |&(offset, weight)| {
+        let mut neighbour_idx = 0;
+
+        for ((coord, offset), size) in coords.iter().zip(offset).zip(dims) {
+            let size = size as isize;
+            let mut neighbour_coord = *coord as isize + offset;
+
+            if !(0..size).contains(&neighbour_coord) {
+                match edges {
+                    EdgeMode::Empty => return None,
+                    EdgeMode::Wall => return Some((None, weight)),
+                    EdgeMode::Torus => neighbour_coord = neighbour_coord.rem_euclid(size),
+                }
+            }
+
+            neighbour_idx = (neighbour_idx * size as usize) + neighbour_coord as usize;
+        }
+
+        Some((Some(neighbour_idx), weight))
+    })
+}
+
+/// Square grid, where each row is stored after the previous one.
+#[derive(Clone, Debug)]
+pub struct SquareLattice {
+    dims: [usize; 2],
+    offsets: Vec<([isize; 2], u32)>,
+    edges: EdgeMode,
+}
+
+impl SquareLattice {
+    pub fn new(rows: usize, cols: usize, neighbourhood: &Neighbourhood, edges: EdgeMode) -> Self {
+        SquareLattice {
+            dims: [rows, cols],
+            offsets: neighbourhood
+                .offsets()
+                .iter()
+                .map(|&((row_offset, col_offset), weight)| ([row_offset, col_offset], weight))
+                .collect(),
+            edges,
+        }
+    }
+}
+
+impl Topology for SquareLattice {
+    fn cell_count(&self) -> usize {
+        self.dims.iter().product()
+    }
+
+    fn neighbours(&self, idx: usize) -> impl Iterator<Item = (Option<usize>, u32)> + '_ {
+        offset_neighbours(self.dims, idx, &self.offsets, self.edges)
+    }
+}
+
+/// Hex grid in axial coordinates, stored as a parallelogram where each row has the same `r`
+/// coordinate and each column has the same `q` coordinate.
+///
+/// Each cell has 6 neighbours: 2 in its own row, 2 in the row above (at `q` and `q + 1`) and 2 in
+/// the row below (at `q - 1` and `q`). This is what you get when each row is drawn half a cell
+/// further to the right than the row above it (see [`parse_hex`]).
+#[derive(Clone, Debug)]
+pub struct HexLattice {
+    dims: [usize; 2],
+    offsets: Vec<([isize; 2], u32)>,
+    edges: EdgeMode,
+}
+
+impl HexLattice {
+    /// Creates a lattice where the neighbours are the cells within `radius` steps, weighted by the
+    /// number of steps (see [`Neighbourhood::with_distance_weights`]). Panics if the radius doesn't
+    /// fit in an `isize`.
+    pub fn new(rows: usize, cols: usize, radius: usize, weights: &[u32], edges: EdgeMode) -> Self {
+        let radius = isize::try_from(radius).expect("Radius is too large to use as an offset");
+        let mut offsets = Vec::new();
+
+        for r_offset in -radius..=radius {
+            for q_offset in -radius..=radius {
+                let distance = (r_offset.abs() + q_offset.abs() + (r_offset + q_offset).abs()) / 2;
+
+                if distance > 0 && distance <= radius {
+                    let weight = distance_weight(weights, distance as usize);
+                    offsets.push(([r_offset, q_offset], weight));
+                }
+            }
+        }
+
+        HexLattice {
+            dims: [rows, cols],
+            offsets,
+            edges,
+        }
+    }
+}
+
+impl Topology for HexLattice {
+    fn cell_count(&self) -> usize {
+        self.dims.iter().product()
+    }
+
+    fn neighbours(&self, idx: usize) -> impl Iterator<Item = (Option<usize>, u32)> + '_ {
+        offset_neighbours(self.dims, idx, &self.offsets, self.edges)
+    }
+}
+
+/// 3D cubic grid, stored as layers of square grids.
+#[derive(Clone, Debug)]
+pub struct CubicLattice {
+    dims: [usize; 3],
+    offsets: Vec<([isize; 3], u32)>,
+    edges: EdgeMode,
+}
+
+impl CubicLattice {
+    /// Creates a lattice where the neighbours are within `radius` (like [`Neighbourhood::new`],
+    /// but in 3D), weighted by their Manhattan distance. Panics if the radius doesn't fit in an
+    /// `isize`.
+    pub fn new(
+        layers: usize,
+        rows: usize,
+        cols: usize,
+        shape: NeighbourhoodShape,
+        radius: usize,
+        weights: &[u32],
+        edges: EdgeMode,
+    ) -> Self {
+        let radius = isize::try_from(radius).expect("Radius is too large to use as an offset");
+        let mut offsets = Vec::new();
+
+        for layer_offset in -radius..=radius {
+            for row_offset in -radius..=radius {
+                for col_offset in -radius..=radius {
+                    let offset = [layer_offset, row_offset, col_offset];
+                    let distance = offset.iter().map(|axis| axis.unsigned_abs()).sum::<usize>();
+
+                    let in_shape = match shape {
+                        NeighbourhoodShape::VonNeumann => distance <= radius as usize,
+                        NeighbourhoodShape::Moore => true,
+                    };
+
+                    if in_shape && distance > 0 {
+                        offsets.push((offset, distance_weight(weights, distance)));
+                    }
+                }
+            }
+        }
+
+        CubicLattice {
+            dims: [layers, rows, cols],
+            offsets,
+            edges,
+        }
+    }
+}
+
+impl Topology for CubicLattice {
+    fn cell_count(&self) -> usize {
+        self.dims.iter().product()
+    }
+
+    fn neighbours(&self, idx: usize) -> impl Iterator<Item = (Option<usize>, u32)> + '_ {
+        offset_neighbours(self.dims, idx, &self.offsets, self.edges)
+    }
+}
+
+/// Parses a hex map, where each non-empty line is a row and whitespace between cells is ignored.
+///
+/// Rows are normally indented by one more space than the row above, so that the cells line up
+/// with their neighbours, e.g.
+///
+/// ```text
+/// @ . @
+///  @ @ .
+///   . @ @
+/// ```
+pub fn parse_hex<T, E>(
+    input: &str,
+    mut parse_cell: impl FnMut(char) -> Result<T, E>,
+) -> Result<Grid<T>, GridError<E>> {
+    let mut rows: Vec<Vec<T>> = Vec::new();
+
+    for (line_idx, line) in input.lines().enumerate() {
+        let line_number = line_idx + 1;
+        let mut row = Vec::new();
+
+        for (char_idx, c) in line.chars().enumerate() {
+            if c.is_whitespace() {
+                continue;
+            }
+
+            let cell = parse_cell(c).map_err(|error| GridError::InvalidCell {
+                line_number,
+                column: char_idx + 1,
+                error,
+            })?;
+
+            row.push(cell);
+        }
+
+        if row.is_empty() {
+            continue;
+        }
+
+        if let Some(first_row) = rows.first()
+            && first_row.len() != row.len()
+        {
+            return Err(GridError::RaggedRow {
+                line_number,
+                expected: first_row.len(),
+                found: row.len(),
+            });
+        }
+
+        rows.push(row);
+    }
+
+    Ok(Grid::try_from(rows).expect("Rows are checked while parsing"))
+}
+
+/// Problem found while parsing a map with several layers.
+#[derive(Debug, PartialEq, Eq)]
+pub enum LayersError<E> {
+    /// Problem within a layer (line numbers are from the start of the input).
+    Layer(GridError<E>),
+
+    /// A layer has different dimensions to the first layer.
+    SizeMismatch {
+        line_number: usize,
+        expected: (usize, usize),
+        found: (usize, usize),
+    },
+}
+
+impl<E: fmt::Display> fmt::Display for LayersError<E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LayersError::Layer(error) => write!(f, "{}", error),
+            LayersError::SizeMismatch {
+                line_number,
+                expected,
+                found,
+            } => write!(
+                f,
+                "Line {}: Layer is {}x{}, but the first layer is {}x{}",
+                line_number, found.0, found.1, expected.0, expected.1
+            ),
+        }
+    }
+}
+
+impl<E: fmt::Debug + fmt::Display> Error for LayersError<E> {}
+
+/// Parses a map with several layers (e.g. for a [`CubicLattice`]), where the layers are separated
+/// by blank lines and each layer is parsed like [`Grid::parse`].
+pub fn parse_layers<T, E>(
+    input: &str,
+    mut parse_cell: impl FnMut(char) -> Result<T, E>,
+) -> Result<Vec<Grid<T>>, LayersError<E>> {
+    let mut layers: Vec<Grid<T>> = Vec::new();
+    let lines = input.lines().collect::<Vec<_>>();
+
+    for (start_idx, layer_lines) in split_layers(&lines) {
+        let first_line_number = start_idx + 1;
+
+        let layer = Grid::parse(&layer_lines.join("\n"), &mut parse_cell).map_err(|error| {
+            // Line numbers are from the start of the layer, so shift them to the whole input
+            LayersError::Layer(match error {
+                GridError::InvalidCell {
+                    line_number,
+                    column,
+                    error,
+                } => GridError::InvalidCell {
+                    line_number: line_number + start_idx,
+                    column,
+                    error,
+                },
+                GridError::RaggedRow {
+                    line_number,
+                    expected,
+                    found,
+                } => GridError::RaggedRow {
+                    line_number: line_number + start_idx,
+                    expected,
+                    found,
+                },
+            })
+        })?;
+
+        if let Some(first_layer) = layers.first()
+            && (first_layer.rows(), first_layer.cols()) != (layer.rows(), layer.cols())
+        {
+            return Err(LayersError::SizeMismatch {
+                line_number: first_line_number,
+                expected: (first_layer.rows(), first_layer.cols()),
+                found: (layer.rows(), layer.cols()),
+            });
+        }
+
+        layers.push(layer);
+    }
+
+    Ok(layers)
+}
+
+/// Splits lines into groups that are separated by blank lines, along with the index of the first
+/// line in each group.
+fn split_layers<'a>(lines: &'a [&'a str]) -> Vec<(usize, &'a [&'a str])> {
+    let mut layers = Vec::new();
+    let mut start_idx = None;
+
+    for (line_idx, line) in lines.iter().enumerate() {
+        match (line.trim().is_empty(), start_idx) {
+            (false, None) => start_idx = Some(line_idx),
+            (true, Some(layer_start)) => {
+                layers.push((layer_start, &lines[layer_start..line_idx]));
+                start_idx = None;
+            }
+            _ => {}
+        }
+    }
+
+    if let Some(layer_start) = start_idx {
+        layers.push((layer_start, &lines[layer_start..]));
+    }
+
+    layers
+}
@@ -0,0 +1,9 @@
+pub mod batteries;
+pub mod bit_grid;
+pub mod grid;
+pub mod image;
+pub mod lattice;
+pub mod paper_rolls;
+pub mod repeated_ids;
+pub mod roll_queries;
+pub mod sparse_rolls;
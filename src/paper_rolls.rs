@@ -0,0 +1,684 @@
+//! Helpers for finding which paper rolls can be accessed by a forklift (day 4).
+
+use std::{collections::HashSet, error::Error, fmt};
+
+use crate::{
+    grid::Grid,
+    lattice::{SquareLattice, Topology},
+};
+
+/// By default, a paper roll is accessible when fewer than this many of the 8 surrounding cells
+/// have rolls.
+pub const ACCESSIBLE_THRESHOLD: u32 = 4;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum CellType {
+    Empty,
+    PaperRoll,
+}
+
+impl TryFrom<char> for CellType {
+    type Error = &'static str;
+
+    fn try_from(value: char) -> Result<Self, Self::Error> {
+        use CellType::*;
+
+        match value {
+            '.' => Ok(Empty),
+            '@' => Ok(PaperRoll),
+            _ => Err("Invalid grid cell"),
+        }
+    }
+}
+
+impl fmt::Display for CellType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CellType::Empty => write!(f, "."),
+            CellType::PaperRoll => write!(f, "@"),
+        }
+    }
+}
+
+/// Shape of the cells around a roll that are checked for other rolls.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum NeighbourhoodShape {
+    /// Cells within a Manhattan distance of the radius, e.g. the 4 orthogonal cells for radius 1.
+    VonNeumann,
+
+    /// Cells within a square of the radius, e.g. the 8 surrounding cells for radius 1.
+    Moore,
+}
+
+/// Offsets to the cells around a roll, and how much a roll in each of those cells counts for.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Neighbourhood {
+    offsets: Vec<((isize, isize), u32)>,
+}
+
+impl Neighbourhood {
+    /// Creates a neighbourhood where every neighbour counts once.
+    ///
+    /// The neighbourhood has `(2 * radius + 1)^2 - 1` cells at most, so large radii use a lot of
+    /// memory. Panics if the radius doesn't fit in an `isize`.
+    pub fn new(shape: NeighbourhoodShape, radius: usize) -> Self {
+        let radius = isize::try_from(radius).expect("Radius is too large to use as an offset");
+        let mut offsets = Vec::new();
+
+        for row_offset in -radius..=radius {
+            for col_offset in -radius..=radius {
+                let in_shape = match shape {
+                    NeighbourhoodShape::VonNeumann => row_offset.abs() + col_offset.abs() <= radius,
+                    NeighbourhoodShape::Moore => true,
+                };
+
+                if in_shape && (row_offset, col_offset) != (0, 0) {
+                    offsets.push(((row_offset, col_offset), 1));
+                }
+            }
+        }
+
+        Neighbourhood { offsets }
+    }
+
+    /// The 8 surrounding cells, which is what the puzzle uses.
+    pub fn moore() -> Self {
+        Self::new(NeighbourhoodShape::Moore, 1)
+    }
+
+    /// The 4 orthogonal cells.
+    pub fn von_neumann() -> Self {
+        Self::new(NeighbourhoodShape::VonNeumann, 1)
+    }
+
+    /// Weights each neighbour by its Manhattan distance, where `weights[0]` is for a distance of 1,
+    /// `weights[1]` is for a distance of 2, etc. (e.g. orthogonal and diagonal for radius 1 Moore).
+    ///
+    /// The last weight is used for any distances after it. Weights only depend on the distance, so
+    /// a roll always counts the same for its neighbour as its neighbour does for it.
+    pub fn with_distance_weights(mut self, weights: &[u32]) -> Self {
+        for ((row_offset, col_offset), weight) in self.offsets.iter_mut() {
+            let distance = row_offset.unsigned_abs() + col_offset.unsigned_abs();
+            *weight = distance_weight(weights, distance);
+        }
+
+        self
+    }
+
+    /// Gets each offset from a roll, along with its weight.
+    pub fn offsets(&self) -> &[((isize, isize), u32)] {
+        &self.offsets
+    }
+}
+
+/// Gets the weight for a neighbour at `distance` (starting at 1), from a list of weights where the
+/// last weight is used for any further distances. Neighbours count once if there aren't any weights.
+pub(crate) fn distance_weight(weights: &[u32], distance: usize) -> u32 {
+    weights
+        .get(distance - 1)
+        .or(weights.last())
+        .copied()
+        .unwrap_or(1)
+}
+
+impl Default for Neighbourhood {
+    fn default() -> Self {
+        Self::moore()
+    }
+}
+
+/// What's beyond the edges of the grid.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum EdgeMode {
+    /// Cells outside the grid are empty, so rolls on the edges have fewer neighbours.
+    #[default]
+    Empty,
+
+    /// Cells outside the grid are walls, which count as rolls that can never be removed.
+    Wall,
+
+    /// The grid wraps around, so the top edge is next to the bottom edge and the left edge is
+    /// next to the right edge.
+    ///
+    /// NOTE: If the neighbourhood is larger than the grid, a cell can be its own neighbour (or
+    /// the same neighbour more than once), and it's counted each time.
+    Torus,
+}
+
+/// Decides which paper rolls are accessible.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Rules {
+    pub neighbourhood: Neighbourhood,
+
+    /// A roll is accessible when the weighted count of its neighbouring rolls is less than this.
+    pub threshold: u32,
+
+    pub edges: EdgeMode,
+}
+
+impl Default for Rules {
+    /// The puzzle's rules, i.e. fewer than 4 rolls in the 8 surrounding cells.
+    fn default() -> Self {
+        Rules {
+            neighbourhood: Neighbourhood::default(),
+            threshold: ACCESSIBLE_THRESHOLD,
+            edges: EdgeMode::default(),
+        }
+    }
+}
+
+#[derive(Debug)]
+struct GridCell {
+    cell_type: CellType,
+
+    /// Weighted count of the neighbouring rolls.
+    occupied: u64,
+}
+
+/// Adds up the weights of the neighbours of a cell that have rolls (or are walls).
+///
+/// Weights can be as large as `u32::MAX`, so they're added up in 64 bits (saturating, rather than
+/// overflowing, for huge neighbourhoods).
+pub(crate) fn occupied_weight(topology: &impl Topology, cells: &[CellType], idx: usize) -> u64 {
+    topology
+        .neighbours(idx)
+        .filter(|&(neighbour_idx, _)| {
+            neighbour_idx.is_none_or(|neighbour_idx| cells[neighbour_idx] == CellType::PaperRoll)
+        })
+        .fold(0, |total: u64, (_, weight)| {
+            total.saturating_add(u64::from(weight))
+        })
+}
+
+/// Counts the paper rolls that are accessible straight away.
+pub fn count_accessible(grid: &Grid<CellType>, rules: &Rules) -> usize {
+    let lattice = SquareLattice::new(grid.rows(), grid.cols(), &rules.neighbourhood, rules.edges);
+    count_accessible_in(&lattice, grid.cells(), rules.threshold)
+}
+
+/// Counts the paper rolls that are accessible straight away, for any layout of cells.
+///
+/// `cells` must be numbered the same way as the topology.
+pub fn count_accessible_in(topology: &impl Topology, cells: &[CellType], threshold: u32) -> usize {
+    debug_assert_eq!(cells.len(), topology.cell_count());
+
+    cells
+        .iter()
+        .enumerate()
+        .filter(|&(idx, cell)| {
+            *cell == CellType::PaperRoll
+                && occupied_weight(topology, cells, idx) < u64::from(threshold)
+        })
+        .count()
+}
+
+/// Repeatedly removes every accessible paper roll, until none of the remaining rolls are
+/// accessible. Returns the positions of the rolls removed in each round.
+///
+/// Rolls in the same round are removed at the same time, so removing one roll doesn't make
+/// another roll accessible until the next round.
+pub fn removal_rounds(grid: &Grid<CellType>, rules: &Rules) -> Vec<Vec<(usize, usize)>> {
+    let lattice = SquareLattice::new(grid.rows(), grid.cols(), &rules.neighbourhood, rules.edges);
+
+    removal_rounds_in(&lattice, grid.cells(), rules.threshold)
+        .into_iter()
+        .map(|round| {
+            round
+                .into_iter()
+                .map(|idx| (idx / grid.cols(), idx % grid.cols()))
+                .collect()
+        })
+        .collect()
+}
+
+/// Repeatedly removes every accessible paper roll for any layout of cells, and returns the indices
+/// of the rolls removed in each round.
+///
+/// Rather than rescanning every cell each round, this keeps a worklist of rolls that became
+/// accessible. Removing a roll decrements its neighbours' counts, and any neighbour that drops
+/// below the threshold is removed in the next round (similar to k-core peeling). Each roll is
+/// visited a fixed number of times, so this runs in `O(cells * neighbours)` overall.
+pub fn removal_rounds_in(
+    topology: &impl Topology,
+    cells: &[CellType],
+    threshold: u32,
+) -> Vec<Vec<usize>> {
+    debug_assert_eq!(cells.len(), topology.cell_count());
+
+    // Calculate initial paper roll adjacency counts
+    let mut cells = cells
+        .iter()
+        .enumerate()
+        .map(|(idx, cell_type)| GridCell {
+            cell_type: *cell_type,
+            occupied: occupied_weight(topology, cells, idx),
+        })
+        .collect::<Vec<_>>();
+
+    let is_accessible = |cell: &GridCell| cell.occupied < u64::from(threshold);
+
+    // The first round is every roll that's already accessible
+    let mut accessible_rolls = cells
+        .iter()
+        .enumerate()
+        .filter(|(_, cell)| cell.cell_type == CellType::PaperRoll && is_accessible(cell))
+        .map(|(idx, _)| idx)
+        .collect::<Vec<_>>();
+
+    let mut rounds = Vec::new();
+
+    while !accessible_rolls.is_empty() {
+        // Clear the whole round first, so that rolls in this round aren't added to the next one
+        for &idx in accessible_rolls.iter() {
+            cells[idx].cell_type = CellType::Empty;
+        }
+
+        let mut next_accessible_rolls = Vec::new();
+
+        for &idx in accessible_rolls.iter() {
+            // Update the counts of the surrounding cells
+            // NOTE: Weights are symmetric, so this roll has the same weight from the neighbour's
+            // point of view. Walls are never removed, so they don't need updating.
+            for (neighbour_idx, weight) in topology.neighbours(idx) {
+                let Some(neighbour_idx) = neighbour_idx else {
+                    continue;
+                };
+
+                let neighbour = &mut cells[neighbour_idx];
+                let was_accessible = is_accessible(neighbour);
+
+                neighbour.occupied = neighbour.occupied.saturating_sub(u64::from(weight));
+
+                // Counts only go down, so each roll becomes accessible at most once
+                if neighbour.cell_type == CellType::PaperRoll
+                    && !was_accessible
+                    && is_accessible(neighbour)
+                {
+                    next_accessible_rolls.push(neighbour_idx);
+                }
+            }
+        }
+
+        rounds.push(accessible_rolls);
+        accessible_rolls = next_accessible_rolls;
+    }
+
+    rounds
+}
+
+/// When a cell's paper roll was removed.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Removal {
+    /// There wasn't a roll in this cell to begin with.
+    NoRoll,
+
+    /// The roll was removed in this round (starting at 1).
+    Round(usize),
+
+    /// The roll can never be removed.
+    Never,
+}
+
+/// Layer map of when each paper roll gets removed.
+#[derive(Clone, Debug)]
+pub struct RemovalTimeline {
+    pub removals: Grid<Removal>,
+
+    /// Number of rolls removed in each round, starting with round 1.
+    pub round_counts: Vec<usize>,
+}
+
+impl RemovalTimeline {
+    pub fn new(grid: &Grid<CellType>, rules: &Rules) -> Self {
+        let mut removals = grid.map(|cell_type| match cell_type {
+            CellType::Empty => Removal::NoRoll,
+            CellType::PaperRoll => Removal::Never,
+        });
+
+        let rounds = removal_rounds(grid, rules);
+        let round_counts = rounds.iter().map(|round| round.len()).collect();
+
+        for (round_idx, round) in rounds.into_iter().enumerate() {
+            for position in round {
+                removals[position] = Removal::Round(round_idx + 1);
+            }
+        }
+
+        RemovalTimeline {
+            removals,
+            round_counts,
+        }
+    }
+
+    /// Gets the stable configuration, i.e. the rolls that are left once nothing else is accessible.
+    pub fn remaining(&self) -> Grid<CellType> {
+        self.removals.map(|removal| match removal {
+            Removal::Never => CellType::PaperRoll,
+            _ => CellType::Empty,
+        })
+    }
+
+    /// Gets the total number of rolls that can be removed.
+    pub fn total_removed(&self) -> usize {
+        self.round_counts.iter().sum()
+    }
+}
+
+/// Reason why a roll couldn't be placed or removed.
+#[derive(Debug, PartialEq, Eq)]
+pub enum EditError {
+    /// The position is outside of the map.
+    OutOfBounds { row: usize, col: usize },
+
+    /// There's already a roll at the position.
+    AlreadyRoll { row: usize, col: usize },
+
+    /// There isn't a roll at the position to remove.
+    NoRoll { row: usize, col: usize },
+}
+
+impl fmt::Display for EditError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            EditError::OutOfBounds { row, col } => {
+                write!(f, "Position ({}, {}) is outside of the map", row, col)
+            }
+            EditError::AlreadyRoll { row, col } => {
+                write!(f, "There's already a roll at ({}, {})", row, col)
+            }
+            EditError::NoRoll { row, col } => write!(f, "There's no roll at ({}, {})", row, col),
+        }
+    }
+}
+
+impl Error for EditError {}
+
+/// Map of paper rolls that can be changed a roll at a time, while keeping track of which rolls are
+/// accessible.
+///
+/// Placing or removing a roll only updates the counts of its neighbours (like the removal rounds
+/// do), so each change takes time proportional to the size of the neighbourhood rather than the map.
+#[derive(Debug)]
+pub struct LiveGrid {
+    cols: usize,
+    lattice: SquareLattice,
+    threshold: u32,
+    cells: Grid<GridCell>,
+    accessible: HashSet<(usize, usize)>,
+}
+
+impl LiveGrid {
+    pub fn new(grid: &Grid<CellType>, rules: &Rules) -> Self {
+        let lattice =
+            SquareLattice::new(grid.rows(), grid.cols(), &rules.neighbourhood, rules.edges);
+
+        let mut live_grid = LiveGrid {
+            cols: grid.cols(),
+            cells: grid.map(|cell_type| GridCell {
+                cell_type: *cell_type,
+                occupied: 0,
+            }),
+            lattice,
+            threshold: rules.threshold,
+            accessible: HashSet::new(),
+        };
+
+        // Calculate initial paper roll adjacency counts
+        for (idx, position) in grid.positions().enumerate() {
+            live_grid.cells[position].occupied =
+                occupied_weight(&live_grid.lattice, grid.cells(), idx);
+            live_grid.update_accessible(position);
+        }
+
+        live_grid
+    }
+
+    /// Places a roll in an empty cell.
+    pub fn place_roll(&mut self, row: usize, col: usize) -> Result<(), EditError> {
+        match self.cells.get(row, col) {
+            None => Err(EditError::OutOfBounds { row, col }),
+            Some(cell) if cell.cell_type == CellType::PaperRoll => {
+                Err(EditError::AlreadyRoll { row, col })
+            }
+            Some(_) => {
+                self.cells[(row, col)].cell_type = CellType::PaperRoll;
+                self.update_neighbours((row, col), |occupied, weight| {
+                    occupied.saturating_add(weight)
+                });
+                Ok(())
+            }
+        }
+    }
+
+    /// Removes a roll, whether or not it's accessible.
+    pub fn remove_roll(&mut self, row: usize, col: usize) -> Result<(), EditError> {
+        match self.cells.get(row, col) {
+            None => Err(EditError::OutOfBounds { row, col }),
+            Some(cell) if cell.cell_type == CellType::Empty => Err(EditError::NoRoll { row, col }),
+            Some(_) => {
+                self.cells[(row, col)].cell_type = CellType::Empty;
+                self.update_neighbours((row, col), |occupied, weight| {
+                    occupied.saturating_sub(weight)
+                });
+                Ok(())
+            }
+        }
+    }
+
+    /// Applies a change in the roll at `position` to the counts of its neighbours (which can
+    /// include the roll itself, when wrapping around a small map).
+    fn update_neighbours(&mut self, position: (usize, usize), update: impl Fn(u64, u64) -> u64) {
+        let idx = (position.0 * self.cols) + position.1;
+
+        for (neighbour_idx, weight) in self.lattice.neighbours(idx) {
+            let Some(neighbour_idx) = neighbour_idx else {
+                continue;
+            };
+
+            let neighbour_position = (neighbour_idx / self.cols, neighbour_idx % self.cols);
+            let neighbour = &mut self.cells[neighbour_position];
+            neighbour.occupied = update(neighbour.occupied, u64::from(weight));
+
+            // NOTE: Can't use update_accessible() here, as the lattice is still borrowed
+            if neighbour.cell_type == CellType::PaperRoll
+                && neighbour.occupied < u64::from(self.threshold)
+            {
+                self.accessible.insert(neighbour_position);
+            } else {
+                self.accessible.remove(&neighbour_position);
+            }
+        }
+
+        self.update_accessible(position);
+    }
+
+    fn update_accessible(&mut self, position: (usize, usize)) {
+        let cell = &self.cells[position];
+
+        if cell.cell_type == CellType::PaperRoll && cell.occupied < u64::from(self.threshold) {
+            self.accessible.insert(position);
+        } else {
+            self.accessible.remove(&position);
+        }
+    }
+
+    pub fn is_accessible(&self, row: usize, col: usize) -> bool {
+        self.accessible.contains(&(row, col))
+    }
+
+    /// Gets the positions of the accessible rolls, in row-major order.
+    pub fn accessible(&self) -> Vec<(usize, usize)> {
+        let mut accessible = self.accessible.iter().copied().collect::<Vec<_>>();
+        accessible.sort_unstable();
+        accessible
+    }
+
+    /// Counts the accessible rolls, without listing them.
+    pub fn accessible_count(&self) -> usize {
+        self.accessible.len()
+    }
+
+    /// Gets the current map.
+    pub fn to_grid(&self) -> Grid<CellType> {
+        self.cells.map(|cell| cell.cell_type)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse_grid(input: &str) -> Grid<CellType> {
+        Grid::parse(input, CellType::try_from).unwrap()
+    }
+
+    #[test]
+    fn transposed_maps_give_the_same_answers() {
+        let rules = Rules::default();
+        let wide = parse_grid(include_str!("../inputs/day4/wide.txt"));
+        let tall = parse_grid(include_str!("../inputs/day4/tall.txt"));
+
+        assert_eq!((wide.rows(), wide.cols()), (tall.cols(), tall.rows()));
+
+        for (grid, transposed) in [(&wide, &tall), (&tall, &wide)] {
+            for ((row, col), cell) in grid.iter() {
+                assert_eq!(cell, &transposed[(col, row)]);
+            }
+        }
+
+        let total_removed = |grid: &Grid<CellType>| {
+            removal_rounds(grid, &rules)
+                .iter()
+                .map(|round| round.len())
+                .sum::<usize>()
+        };
+
+        assert_eq!(count_accessible(&wide, &rules), 11);
+        assert_eq!(count_accessible(&tall, &rules), 11);
+        assert_eq!(total_removed(&wide), 70);
+        assert_eq!(total_removed(&tall), 70);
+    }
+
+    #[test]
+    fn large_weights_dont_overflow() {
+        // 2 neighbours at this weight would wrap around to a count of 2 in 32 bits
+        let rules = Rules {
+            neighbourhood: Neighbourhood::moore().with_distance_weights(&[(1 << 31) + 1]),
+            ..Rules::default()
+        };
+
+        let grid = parse_grid("@@@");
+        assert_eq!(count_accessible(&grid, &rules), 0);
+        assert!(removal_rounds(&grid, &rules).is_empty());
+
+        let mut live_grid = LiveGrid::new(&parse_grid("@.@"), &rules);
+        assert_eq!(live_grid.accessible(), [(0, 0), (0, 2)]);
+
+        live_grid.place_roll(0, 1).unwrap();
+        assert_eq!(live_grid.accessible(), []);
+
+        live_grid.remove_roll(0, 1).unwrap();
+        assert_eq!(live_grid.accessible(), [(0, 0), (0, 2)]);
+    }
+
+    /// Checks the live accessible rolls against counting them from scratch.
+    fn assert_matches_fresh_count(live_grid: &LiveGrid, rules: &Rules) {
+        let grid = live_grid.to_grid();
+
+        let mut expected = removal_rounds(&grid, rules)
+            .into_iter()
+            .next()
+            .unwrap_or_default();
+        expected.sort_unstable();
+
+        assert_eq!(live_grid.accessible(), expected);
+        assert_eq!(live_grid.accessible_count(), count_accessible(&grid, rules));
+
+        for ((row, col), _) in grid.iter() {
+            assert_eq!(
+                live_grid.is_accessible(row, col),
+                expected.contains(&(row, col))
+            );
+        }
+    }
+
+    #[test]
+    fn live_grid_matches_a_fresh_count_after_edits() {
+        let example = parse_grid(include_str!("../inputs/day4/example.txt"));
+        let mut state = 0x2025_0050_u64;
+
+        for edges in [EdgeMode::Empty, EdgeMode::Wall, EdgeMode::Torus] {
+            for neighbourhood in [
+                Neighbourhood::moore(),
+                Neighbourhood::new(NeighbourhoodShape::VonNeumann, 2)
+                    .with_distance_weights(&[3, 1]),
+            ] {
+                let rules = Rules {
+                    neighbourhood,
+                    threshold: ACCESSIBLE_THRESHOLD,
+                    edges,
+                };
+
+                let mut live_grid = LiveGrid::new(&example, &rules);
+                assert_matches_fresh_count(&live_grid, &rules);
+
+                for _ in 0..200 {
+                    // xorshift64, so that failures can be reproduced
+                    state ^= state << 13;
+                    state ^= state >> 7;
+                    state ^= state << 17;
+
+                    let (row, col) = ((state % 10) as usize, ((state / 10) % 10) as usize);
+
+                    if live_grid.to_grid()[(row, col)] == CellType::PaperRoll {
+                        live_grid.remove_roll(row, col).unwrap();
+                    } else {
+                        live_grid.place_roll(row, col).unwrap();
+                    }
+
+                    assert_matches_fresh_count(&live_grid, &rules);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn live_grid_rejects_invalid_edits() {
+        let mut live_grid = LiveGrid::new(&parse_grid("@.\n.."), &Rules::default());
+
+        assert_eq!(
+            live_grid.place_roll(0, 0),
+            Err(EditError::AlreadyRoll { row: 0, col: 0 })
+        );
+        assert_eq!(
+            live_grid.remove_roll(0, 1),
+            Err(EditError::NoRoll { row: 0, col: 1 })
+        );
+        assert_eq!(
+            live_grid.place_roll(2, 0),
+            Err(EditError::OutOfBounds { row: 2, col: 0 })
+        );
+
+        // Failed edits don't change anything
+        assert_eq!(live_grid.to_grid(), parse_grid("@.\n.."));
+        assert_eq!(live_grid.accessible(), [(0, 0)]);
+    }
+
+    #[test]
+    fn live_grid_counts_itself_when_wrapping_around() {
+        // On a 1x1 torus, every neighbour is the roll itself
+        let rules = Rules {
+            edges: EdgeMode::Torus,
+            ..Rules::default()
+        };
+        let mut live_grid = LiveGrid::new(&parse_grid("."), &rules);
+
+        live_grid.place_roll(0, 0).unwrap();
+        assert_matches_fresh_count(&live_grid, &rules);
+        assert_eq!(live_grid.accessible_count(), 0);
+
+        live_grid.remove_roll(0, 0).unwrap();
+        assert_matches_fresh_count(&live_grid, &rules);
+        assert_eq!(live_grid.to_grid(), parse_grid("."));
+    }
+}
@@ -0,0 +1,267 @@
+//! Helpers for IDs made from a chunk of digits that's repeated several times, e.g. `123123`.
+//!
+//! These were originally written for day 2, where these IDs are invalid.
+
+use std::ops::RangeInclusive;
+
+/// Rule for deciding whether an ID is invalid.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Rule {
+    /// ID is made from 2 repeated halves, e.g. `123123` (day 2, part 1).
+    Halves,
+
+    /// ID is made from 2 or more repeated chunks, e.g. `121212` (day 2, part 2).
+    RepeatedChunks,
+}
+
+impl Rule {
+    /// Gets the chunk counts to check for IDs with up to `max_digits` digits.
+    pub fn chunk_counts(self, max_digits: usize) -> RangeInclusive<usize> {
+        match self {
+            Rule::Halves => 2..=2,
+            Rule::RepeatedChunks => 2..=max_digits.max(2),
+        }
+    }
+}
+
+/// Checks whether an ID is invalid under the given rule.
+///
+/// Runs in time proportional to the digit count (times the number of chunk counts to check).
+pub fn is_invalid(id: u64, rule: Rule) -> bool {
+    let id_str = id.to_string();
+    let digit_count = id_str.len();
+
+    rule.chunk_counts(digit_count)
+        .filter(|chunk_count| digit_count.is_multiple_of(*chunk_count))
+        .any(|chunk_count| {
+            let chunk = &id_str[0..(digit_count / chunk_count)];
+            chunk.repeat(chunk_count) == id_str
+        })
+}
+
+/// Finds the smallest invalid ID that's strictly greater than `id`.
+///
+/// Returns `None` if the next invalid ID doesn't fit in a `u64`. This doesn't look at any of the
+/// IDs in-between, so it takes the same time regardless of how far away the next invalid ID is.
+pub fn next_invalid_after(id: u64, rule: Rule) -> Option<u64> {
+    let target = id.checked_add(1)?.to_string();
+
+    // The next invalid ID has at most 1 more digit than the target, e.g. 999 => 1111
+    rule.chunk_counts(target.len() + 1)
+        .filter_map(|chunk_count| {
+            let chunk = get_starting_number(&target, chunk_count);
+            chunk.to_string().repeat(chunk_count).parse::<u64>().ok()
+        })
+        .min()
+}
+
+/// Finds the largest invalid ID that's strictly less than `id`.
+///
+/// Returns `None` if there isn't one, e.g. for anything up to `11`. Like [`next_invalid_after`],
+/// this doesn't depend on how far away the previous invalid ID is.
+pub fn prev_invalid_before(id: u64, rule: Rule) -> Option<u64> {
+    let target = id.checked_sub(1)?.to_string();
+
+    rule.chunk_counts(target.len())
+        .filter_map(|chunk_count| {
+            let chunk = get_ending_number(&target, chunk_count);
+            (chunk > 0).then(|| chunk.to_string().repeat(chunk_count).parse::<u64>().ok())?
+        })
+        .max()
+}
+
+/// More generalised way of getting the starting number to check.
+///
+/// This is the smallest chunk which, when repeated `chunk_count` times, is `>= range_start`.
+pub fn get_starting_number(range_start: &str, chunk_count: usize) -> u64 {
+    /*
+     * Examples:
+     * - 12345678, Chunk Count 2
+     *   - Chunk Cutoff: Up to 4th Digit => 1234
+     *   - Chunks are split exactly
+     *     - 1st Chunk = 1234, 2nd Chunk = 5678
+     *     - 12341234 < 12345678, so start at 1234 + 1
+     * - 12345678, Chunk Count 3
+     *   - Chunk Cutoff: Up to 2nd Digit => 12
+     *   - Chunks aren't split exactly
+     *     - Start at 10 ^ Cutoff => 10^2 = 100
+     */
+    let digit_count = range_start.chars().count();
+    let cutoff_idx = digit_count / chunk_count;
+
+    if digit_count.is_multiple_of(chunk_count) {
+        // Exact Split Case
+        // - If 1st chunk repeated >= range start, start at 1st chunk
+        // - Otherwise, start at 1st chunk + 1
+        // NOTE: Comparing the 1st and 2nd chunks isn't enough for 3+ chunks, e.g. 121299
+        let chunk_1 = &range_start[0..cutoff_idx];
+
+        let chunk_1_parsed = chunk_1.parse::<u64>().unwrap();
+        if chunk_1.repeat(chunk_count).as_str() >= range_start {
+            chunk_1_parsed
+        } else {
+            chunk_1_parsed + 1
+        }
+    } else {
+        // Non-Exact Split Case
+        // Start at 10 ^ Cutoff
+        10_u64.pow(cutoff_idx as u32)
+    }
+}
+
+/// More generalised way of getting the ending number to check.
+///
+/// This is the largest chunk which, when repeated `chunk_count` times, is `<= range_end`. If there
+/// isn't one, e.g. for `10` with 2 chunks, this is `0`.
+pub fn get_ending_number(range_end: &str, chunk_count: usize) -> u64 {
+    /*
+     * Examples:
+     * - 12345678, Chunk Count 2
+     *   - Chunk Cutoff: Up to 4th Digit => 1234
+     *   - Chunks are split exactly
+     *     - 1st Chunk = 1234, 2nd Chunk = 5678
+     *     - 12341234 <= 12345678, so end at 1234
+     * - 12345678, Chunk Count 3
+     *   - Chunk Cutoff: Up to 2nd Digit => 12
+     *   - Chunks aren't split exactly
+     *     - End at 10 ^ Cutoff - 1 => 10^2 - 1 = 99
+     */
+    let digit_count = range_end.chars().count();
+    let cutoff_idx = digit_count / chunk_count;
+
+    if digit_count.is_multiple_of(chunk_count) {
+        // Exact Split Case
+        // - If 1st chunk repeated <= range end, end at 1st chunk
+        // - Otherwise, end at 1st chunk - 1
+        let chunk_1 = &range_end[0..cutoff_idx];
+
+        let chunk_1_parsed = chunk_1.parse::<u64>().unwrap();
+        if chunk_1.repeat(chunk_count).as_str() <= range_end {
+            chunk_1_parsed
+        } else {
+            chunk_1_parsed - 1
+        }
+    } else {
+        // Non-Exact Split Case
+        // End at 10 ^ Cutoff - 1
+        10_u64.pow(cutoff_idx as u32) - 1
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const RULES: [Rule; 2] = [Rule::Halves, Rule::RepeatedChunks];
+
+    /// Largest ID with 2 repeated halves that fits in a `u64`.
+    const LARGEST_INVALID_ID: u64 = 18_446_744_071_844_674_407;
+
+    #[test]
+    fn checks_ids() {
+        for id in [11, 1010, 123123, 99999999] {
+            assert!(is_invalid(id, Rule::Halves), "{}", id);
+            assert!(is_invalid(id, Rule::RepeatedChunks), "{}", id);
+        }
+
+        for id in [111, 121212, 1111111] {
+            assert!(!is_invalid(id, Rule::Halves), "{}", id);
+            assert!(is_invalid(id, Rule::RepeatedChunks), "{}", id);
+        }
+
+        for id in [0, 1, 9, 10, 12, 1011, 121299, 1231234] {
+            assert!(!is_invalid(id, Rule::Halves), "{}", id);
+            assert!(!is_invalid(id, Rule::RepeatedChunks), "{}", id);
+        }
+    }
+
+    #[test]
+    fn rolls_over_to_more_digits() {
+        assert_eq!(next_invalid_after(99, Rule::RepeatedChunks), Some(111));
+        assert_eq!(next_invalid_after(99, Rule::Halves), Some(1010));
+        assert_eq!(next_invalid_after(999, Rule::RepeatedChunks), Some(1010));
+        assert_eq!(next_invalid_after(999, Rule::Halves), Some(1010));
+
+        assert_eq!(prev_invalid_before(111, Rule::RepeatedChunks), Some(99));
+        assert_eq!(prev_invalid_before(1010, Rule::RepeatedChunks), Some(999));
+        assert_eq!(prev_invalid_before(1010, Rule::Halves), Some(99));
+    }
+
+    #[test]
+    fn compares_every_chunk_for_3_or_more_chunks() {
+        // The 1st and 2nd chunks of 121299 match, but the 3rd chunk is larger
+        assert_eq!(get_starting_number("121299", 3), 13);
+        assert_eq!(get_ending_number("121299", 3), 12);
+        assert_eq!(get_starting_number("121211", 3), 12);
+        assert_eq!(get_ending_number("121211", 3), 11);
+
+        assert_eq!(
+            next_invalid_after(121211, Rule::RepeatedChunks),
+            Some(121212)
+        );
+        assert_eq!(
+            next_invalid_after(121212, Rule::RepeatedChunks),
+            Some(122122)
+        );
+        assert_eq!(
+            prev_invalid_before(121299, Rule::RepeatedChunks),
+            Some(121212)
+        );
+        assert_eq!(prev_invalid_before(121299, Rule::Halves), Some(121121));
+    }
+
+    #[test]
+    fn has_nothing_before_11() {
+        for rule in RULES {
+            for id in 0..=11 {
+                assert_eq!(prev_invalid_before(id, rule), None, "{}", id);
+            }
+
+            assert_eq!(prev_invalid_before(12, rule), Some(11));
+        }
+    }
+
+    #[test]
+    fn has_nothing_past_the_largest_u64() {
+        for rule in RULES {
+            assert_eq!(
+                next_invalid_after(LARGEST_INVALID_ID - 1, rule),
+                Some(LARGEST_INVALID_ID)
+            );
+            assert_eq!(next_invalid_after(LARGEST_INVALID_ID, rule), None);
+            assert_eq!(next_invalid_after(u64::MAX - 1, rule), None);
+            assert_eq!(next_invalid_after(u64::MAX, rule), None);
+
+            assert_eq!(
+                prev_invalid_before(u64::MAX, rule),
+                Some(LARGEST_INVALID_ID)
+            );
+        }
+
+        assert!(is_invalid(LARGEST_INVALID_ID, Rule::Halves));
+        assert!(!is_invalid(u64::MAX, Rule::RepeatedChunks));
+    }
+
+    #[test]
+    fn matches_scanning_every_id() {
+        for rule in RULES {
+            let invalid_ids = (0..=200_000)
+                .filter(|id| is_invalid(*id, rule))
+                .collect::<Vec<_>>();
+
+            for pair in invalid_ids.windows(2) {
+                let (previous, next) = (pair[0], pair[1]);
+
+                assert_eq!(next_invalid_after(previous, rule), Some(next));
+                assert_eq!(prev_invalid_before(next, rule), Some(previous));
+
+                // IDs in-between skip straight to the invalid IDs on either side
+                let middle = previous + ((next - previous) / 2);
+                if middle != previous {
+                    assert_eq!(next_invalid_after(middle, rule), Some(next));
+                    assert_eq!(prev_invalid_before(middle + 1, rule), Some(previous));
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,372 @@
+//! "What-if" queries about removing paper rolls (day 4), e.g. which rolls need to be removed
+//! before a specific roll can be reached.
+
+use std::{collections::HashMap, error::Error, fmt};
+
+use crate::{
+    grid::Grid,
+    lattice::{SquareLattice, Topology},
+    paper_rolls::{CellType, Rules, occupied_weight, removal_rounds_in},
+};
+
+/// Reason why the rolls that need to be removed before reaching a roll couldn't be found.
+#[derive(Debug, PartialEq, Eq)]
+pub enum QueryError {
+    /// The position is outside of the map.
+    OutOfBounds { row: usize, col: usize },
+
+    /// There isn't a roll at the position.
+    NoRoll { row: usize, col: usize },
+
+    /// The roll is part of the stable core, so it can never be reached.
+    Unreachable { row: usize, col: usize },
+}
+
+impl fmt::Display for QueryError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            QueryError::OutOfBounds { row, col } => {
+                write!(f, "Position ({}, {}) is outside of the map", row, col)
+            }
+            QueryError::NoRoll { row, col } => write!(f, "There's no roll at ({}, {})", row, col),
+            QueryError::Unreachable { row, col } => write!(
+                f,
+                "Roll at ({}, {}) can never be reached, as it's part of the stable core",
+                row, col
+            ),
+        }
+    }
+}
+
+impl Error for QueryError {}
+
+/// Finds the rolls that need to be removed before the roll at `target` becomes accessible, in an
+/// order they can be removed in.
+///
+/// The result is minimal in the sense that leaving out any one of the rolls doesn't work, but
+/// there could be a smaller set that uses different rolls. It's empty if the roll is already
+/// accessible.
+pub fn dependency_set(
+    grid: &Grid<CellType>,
+    rules: &Rules,
+    (row, col): (usize, usize),
+) -> Result<Vec<(usize, usize)>, QueryError> {
+    match grid.get(row, col) {
+        None => return Err(QueryError::OutOfBounds { row, col }),
+        Some(CellType::Empty) => return Err(QueryError::NoRoll { row, col }),
+        Some(CellType::PaperRoll) => {}
+    }
+
+    let lattice = SquareLattice::new(grid.rows(), grid.cols(), &rules.neighbourhood, rules.edges);
+    let target = (row * grid.cols()) + col;
+
+    let dependencies = dependency_set_in(&lattice, grid.cells(), rules.threshold, target)
+        .ok_or(QueryError::Unreachable { row, col })?;
+
+    Ok(dependencies
+        .into_iter()
+        .map(|idx| (idx / grid.cols(), idx % grid.cols()))
+        .collect())
+}
+
+/// Finds the rolls that need to be removed before the roll at `target` becomes accessible, for any
+/// layout of cells (see [`dependency_set`]). Returns `None` if it can never become accessible.
+///
+/// This starts from every roll that was removed before the target, and that the target depends
+/// on (i.e. a neighbour that was removed in an earlier round, or one of that neighbour's
+/// dependencies, etc.). Then it tries leaving out each of those rolls, latest first, and keeps
+/// going until none of them can be left out.
+pub fn dependency_set_in(
+    topology: &impl Topology,
+    cells: &[CellType],
+    threshold: u32,
+    target: usize,
+) -> Option<Vec<usize>> {
+    let mut removal_round = vec![None; cells.len()];
+    for (round_idx, round) in removal_rounds_in(topology, cells, threshold)
+        .into_iter()
+        .enumerate()
+    {
+        for idx in round {
+            removal_round[idx] = Some(round_idx);
+        }
+    }
+
+    // Rolls that are never removed can't become accessible
+    removal_round[target]?;
+
+    // Find everything the target depends on, by following neighbours into earlier rounds
+    let mut candidates = Vec::new();
+    let mut is_candidate = vec![false; cells.len()];
+    let mut stack = vec![target];
+
+    while let Some(idx) = stack.pop() {
+        let round = removal_round[idx].expect("Dependencies are always removed");
+
+        for (neighbour_idx, _) in topology.neighbours(idx) {
+            let Some(neighbour_idx) = neighbour_idx else {
+                continue;
+            };
+
+            if !is_candidate[neighbour_idx]
+                && removal_round[neighbour_idx]
+                    .is_some_and(|neighbour_round| neighbour_round < round)
+            {
+                is_candidate[neighbour_idx] = true;
+                candidates.push(neighbour_idx);
+                stack.push(neighbour_idx);
+            }
+        }
+    }
+
+    // Try leaving out the latest rolls first, as the earlier ones are more likely to be needed
+    candidates.sort_unstable_by_key(|idx| (removal_round[*idx], *idx));
+
+    let mut order = removal_order(topology, cells, threshold, &candidates, target)
+        .expect("Removing everything the target depends on always works");
+
+    loop {
+        let mut changed = false;
+
+        for candidate_idx in (0..candidates.len()).rev() {
+            let mut without = candidates.clone();
+            without.remove(candidate_idx);
+
+            if let Some(without_order) = removal_order(topology, cells, threshold, &without, target)
+            {
+                candidates = without;
+                order = without_order;
+                changed = true;
+            }
+        }
+
+        // Leaving out one roll can mean another roll isn't needed any more, so repeat until
+        // nothing changes
+        if !changed {
+            break;
+        }
+    }
+
+    Some(order)
+}
+
+/// Checks whether removing only the rolls in `removals` (in some order) makes the target
+/// accessible, and returns that order if it does.
+fn removal_order(
+    topology: &impl Topology,
+    cells: &[CellType],
+    threshold: u32,
+    removals: &[usize],
+    target: usize,
+) -> Option<Vec<usize>> {
+    // Track the counts of just the rolls in the set, and the target
+    let mut occupied = removals
+        .iter()
+        .chain([&target])
+        .map(|&idx| (idx, occupied_weight(topology, cells, idx)))
+        .collect::<HashMap<_, _>>();
+
+    let mut order = Vec::with_capacity(removals.len());
+    let mut remaining = removals.to_vec();
+
+    while !remaining.is_empty() {
+        // Remove everything that's accessible at the same time, like a removal round
+        let (accessible, not_accessible): (Vec<_>, Vec<_>) = remaining
+            .iter()
+            .partition(|idx| occupied[*idx] < u64::from(threshold));
+
+        if accessible.is_empty() {
+            return None;
+        }
+
+        for &idx in accessible.iter() {
+            for (neighbour_idx, weight) in topology.neighbours(idx) {
+                if let Some(count) = neighbour_idx.and_then(|idx| occupied.get_mut(&idx)) {
+                    *count = count.saturating_sub(u64::from(weight));
+                }
+            }
+        }
+
+        order.extend(accessible);
+        remaining = not_accessible;
+    }
+
+    (occupied[&target] < u64::from(threshold)).then_some(order)
+}
+
+/// Finds the rolls that can never be removed, no matter which other rolls are removed first.
+///
+/// Removing a roll never makes another roll harder to reach, so these are exactly the rolls that
+/// are left once the removal rounds finish (in row-major order).
+pub fn stable_core(grid: &Grid<CellType>, rules: &Rules) -> Vec<(usize, usize)> {
+    let lattice = SquareLattice::new(grid.rows(), grid.cols(), &rules.neighbourhood, rules.edges);
+
+    stable_core_in(&lattice, grid.cells(), rules.threshold)
+        .into_iter()
+        .map(|idx| (idx / grid.cols(), idx % grid.cols()))
+        .collect()
+}
+
+/// Finds the rolls that can never be removed, for any layout of cells (see [`stable_core`]).
+pub fn stable_core_in(topology: &impl Topology, cells: &[CellType], threshold: u32) -> Vec<usize> {
+    let mut is_removed = vec![false; cells.len()];
+    for idx in removal_rounds_in(topology, cells, threshold)
+        .into_iter()
+        .flatten()
+    {
+        is_removed[idx] = true;
+    }
+
+    cells
+        .iter()
+        .enumerate()
+        .filter(|&(idx, cell)| *cell == CellType::PaperRoll && !is_removed[idx])
+        .map(|(idx, _)| idx)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::paper_rolls::{EdgeMode, removal_rounds};
+
+    fn example() -> Grid<CellType> {
+        Grid::parse(
+            include_str!("../inputs/day4/example.txt"),
+            CellType::try_from,
+        )
+        .unwrap()
+    }
+
+    fn is_accessible(grid: &Grid<CellType>, rules: &Rules, position: (usize, usize)) -> bool {
+        removal_rounds(grid, rules)
+            .first()
+            .is_some_and(|round| round.contains(&position))
+    }
+
+    /// Removes as many of `removals` as possible (in any order), then checks whether the target is
+    /// accessible. Removing a roll never makes another roll harder to reach, so this is the best
+    /// that can be done with those rolls.
+    fn reaches_with(
+        grid: &Grid<CellType>,
+        rules: &Rules,
+        removals: &[(usize, usize)],
+        target: (usize, usize),
+    ) -> bool {
+        let mut grid = grid.clone();
+        let mut remaining = removals.to_vec();
+
+        while let Some(idx) = remaining
+            .iter()
+            .position(|&position| is_accessible(&grid, rules, position))
+        {
+            grid[remaining.swap_remove(idx)] = CellType::Empty;
+        }
+
+        is_accessible(&grid, rules, target)
+    }
+
+    #[test]
+    fn dependencies_make_the_target_accessible() {
+        let grid = example();
+        let wall_rules = Rules {
+            edges: EdgeMode::Wall,
+            ..Rules::default()
+        };
+
+        for rules in [Rules::default(), wall_rules] {
+            let core = stable_core(&grid, &rules);
+
+            for (target, cell) in grid.iter() {
+                if *cell == CellType::Empty {
+                    continue;
+                }
+
+                let dependencies = match dependency_set(&grid, &rules, target) {
+                    Ok(dependencies) => dependencies,
+                    Err(err) => {
+                        assert_eq!(
+                            err,
+                            QueryError::Unreachable {
+                                row: target.0,
+                                col: target.1
+                            }
+                        );
+                        assert!(core.contains(&target), "{:?}", target);
+                        continue;
+                    }
+                };
+
+                assert!(!core.contains(&target), "{:?}", target);
+                assert!(!dependencies.contains(&target), "{:?}", target);
+
+                // Removing them in order works, with each one accessible when it's removed
+                let mut removed = grid.clone();
+                for &position in dependencies.iter() {
+                    assert!(
+                        is_accessible(&removed, &rules, position),
+                        "{:?} isn't accessible when reaching {:?}",
+                        position,
+                        target
+                    );
+                    removed[position] = CellType::Empty;
+                }
+
+                assert!(is_accessible(&removed, &rules, target), "{:?}", target);
+
+                // Leaving any one of them out doesn't work
+                for skipped_idx in 0..dependencies.len() {
+                    let mut without = dependencies.clone();
+                    let skipped = without.remove(skipped_idx);
+
+                    assert!(
+                        !reaches_with(&grid, &rules, &without, target),
+                        "{:?} isn't needed to reach {:?}",
+                        skipped,
+                        target
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn rejects_positions_without_rolls() {
+        let grid = example();
+        let rules = Rules::default();
+
+        assert_eq!(
+            dependency_set(&grid, &rules, (0, 0)),
+            Err(QueryError::NoRoll { row: 0, col: 0 })
+        );
+        assert_eq!(
+            dependency_set(&grid, &rules, (10, 0)),
+            Err(QueryError::OutOfBounds { row: 10, col: 0 })
+        );
+        assert_eq!(dependency_set(&grid, &rules, (0, 2)), Ok(Vec::new()));
+    }
+
+    #[test]
+    fn stable_core_is_left_after_the_removal_rounds() {
+        let grid = example();
+        let rules = Rules::default();
+        let core = stable_core(&grid, &rules);
+
+        // 71 rolls, and 43 of them can be removed
+        assert_eq!(core.len(), 71 - 43);
+
+        let mut remaining = grid.clone();
+        for position in removal_rounds(&grid, &rules).into_iter().flatten() {
+            remaining[position] = CellType::Empty;
+        }
+
+        assert_eq!(
+            remaining
+                .iter()
+                .filter(|(_, cell)| **cell == CellType::PaperRoll)
+                .map(|(position, _)| position)
+                .collect::<Vec<_>>(),
+            core
+        );
+    }
+}
@@ -0,0 +1,291 @@
+//! Paper rolls stored as a list of coordinates, for maps that are huge but mostly empty (day 4).
+//!
+//! Only the rolls are numbered as cells, so the peeling logic in `paper_rolls` runs in time
+//! proportional to the number of rolls rather than the area of the map.
+
+use std::{collections::HashMap, error::Error, fmt};
+
+use crate::{
+    grid::GridError,
+    lattice::Topology,
+    paper_rolls::{CellType, EdgeMode, Rules, count_accessible_in, removal_rounds_in},
+};
+
+/// Line in a coordinate list that can't be used (line numbers start at 1).
+#[derive(Debug, PartialEq, Eq)]
+pub enum CoordinateError {
+    /// A line isn't a pair of coordinates.
+    InvalidLine { line_number: usize, line: String },
+
+    /// The same roll is listed more than once.
+    DuplicateRoll {
+        line_number: usize,
+        row: usize,
+        col: usize,
+    },
+}
+
+impl fmt::Display for CoordinateError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CoordinateError::InvalidLine { line_number, line } => write!(
+                f,
+                "Line {}: Expected 'row,col', but found '{}'",
+                line_number, line
+            ),
+            CoordinateError::DuplicateRoll {
+                line_number,
+                row,
+                col,
+            } => write!(
+                f,
+                "Line {}: Roll at ({}, {}) is listed more than once",
+                line_number, row, col
+            ),
+        }
+    }
+}
+
+impl Error for CoordinateError {}
+
+/// Positions of the paper rolls in a map, without storing any of the empty cells.
+#[derive(Clone, Debug)]
+pub struct SparseRolls {
+    rows: usize,
+    cols: usize,
+    positions: Vec<(usize, usize)>,
+
+    /// Index of each roll in `positions`.
+    indices: HashMap<(usize, usize), usize>,
+}
+
+impl SparseRolls {
+    /// Parses a `.`/`@` map, like [`Grid::parse`](crate::grid::Grid::parse), but only keeps the
+    /// positions of the rolls.
+    pub fn parse_map(input: &str) -> Result<Self, GridError<&'static str>> {
+        let mut positions = Vec::new();
+        let mut rows = 0;
+        let mut cols = 0;
+
+        for (line_idx, line) in input.lines().enumerate() {
+            let line_number = line_idx + 1;
+            let trimmed_line = line.trim();
+
+            if trimmed_line.is_empty() {
+                continue;
+            }
+
+            let leading_chars = line.chars().count() - line.trim_start().chars().count();
+            let mut row_len = 0;
+
+            for (char_idx, c) in trimmed_line.chars().enumerate() {
+                let cell = CellType::try_from(c).map_err(|error| GridError::InvalidCell {
+                    line_number,
+                    column: leading_chars + char_idx + 1,
+                    error,
+                })?;
+
+                if cell == CellType::PaperRoll {
+                    positions.push((rows, char_idx));
+                }
+
+                row_len += 1;
+            }
+
+            if rows == 0 {
+                cols = row_len;
+            } else if row_len != cols {
+                return Err(GridError::RaggedRow {
+                    line_number,
+                    expected: cols,
+                    found: row_len,
+                });
+            }
+
+            rows += 1;
+        }
+
+        let indices = index_positions(&positions);
+
+        Ok(SparseRolls {
+            rows,
+            cols,
+            positions,
+            indices,
+        })
+    }
+
+    /// Parses a list of roll coordinates, with one `row,col` pair (starting at 0) per line.
+    ///
+    /// Blank lines and lines starting with `#` are ignored. The map is assumed to be just large
+    /// enough to fit every roll, which only matters for walls and wrapping around the edges.
+    pub fn parse_coordinates(input: &str) -> Result<Self, CoordinateError> {
+        let mut positions = Vec::new();
+        let mut indices = HashMap::new();
+
+        for (line_idx, line) in input.lines().enumerate() {
+            let line_number = line_idx + 1;
+            let trimmed_line = line.trim();
+
+            if trimmed_line.is_empty() || trimmed_line.starts_with('#') {
+                continue;
+            }
+
+            let (row, col) = trimmed_line
+                .split_once(',')
+                .and_then(|(row, col)| {
+                    Some((
+                        row.trim().parse::<usize>().ok()?,
+                        col.trim().parse::<usize>().ok()?,
+                    ))
+                })
+                .ok_or_else(|| CoordinateError::InvalidLine {
+                    line_number,
+                    line: trimmed_line.to_string(),
+                })?;
+
+            if indices.insert((row, col), positions.len()).is_some() {
+                return Err(CoordinateError::DuplicateRoll {
+                    line_number,
+                    row,
+                    col,
+                });
+            }
+
+            positions.push((row, col));
+        }
+
+        // NOTE: Saturating, as a roll at usize::MAX can't have anything beyond it anyway
+        let rows = positions
+            .iter()
+            .map(|(row, _)| row.saturating_add(1))
+            .max()
+            .unwrap_or(0);
+        let cols = positions
+            .iter()
+            .map(|(_, col)| col.saturating_add(1))
+            .max()
+            .unwrap_or(0);
+
+        Ok(SparseRolls {
+            rows,
+            cols,
+            positions,
+            indices,
+        })
+    }
+
+    pub const fn rows(&self) -> usize {
+        self.rows
+    }
+
+    pub const fn cols(&self) -> usize {
+        self.cols
+    }
+
+    /// Number of rolls.
+    pub fn len(&self) -> usize {
+        self.positions.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.positions.is_empty()
+    }
+
+    /// Gets the position of each roll, in the order they were read.
+    pub fn positions(&self) -> &[(usize, usize)] {
+        &self.positions
+    }
+
+    /// Creates a topology where each roll is a cell, and its neighbours are the other rolls.
+    pub fn lattice<'a>(&'a self, rules: &'a Rules) -> SparseLattice<'a> {
+        SparseLattice { rolls: self, rules }
+    }
+
+    /// Counts the paper rolls that are accessible straight away.
+    pub fn count_accessible(&self, rules: &Rules) -> usize {
+        let cells = vec![CellType::PaperRoll; self.len()];
+        count_accessible_in(&self.lattice(rules), &cells, rules.threshold)
+    }
+
+    /// Finds the positions of the rolls removed in each round (see
+    /// [`removal_rounds`](crate::paper_rolls::removal_rounds)).
+    pub fn removal_rounds(&self, rules: &Rules) -> Vec<Vec<(usize, usize)>> {
+        let cells = vec![CellType::PaperRoll; self.len()];
+
+        removal_rounds_in(&self.lattice(rules), &cells, rules.threshold)
+            .into_iter()
+            .map(|round| round.into_iter().map(|idx| self.positions[idx]).collect())
+            .collect()
+    }
+}
+
+fn index_positions(positions: &[(usize, usize)]) -> HashMap<(usize, usize), usize> {
+    positions
+        .iter()
+        .enumerate()
+        .map(|(idx, position)| (*position, idx))
+        .collect()
+}
+
+/// Topology over the rolls in a [`SparseRolls`], where empty cells aren't numbered at all.
+pub struct SparseLattice<'a> {
+    rolls: &'a SparseRolls,
+    rules: &'a Rules,
+}
+
+impl SparseLattice<'_> {
+    /// Gets the position at an offset, applying the edge mode if it's outside the map. Returns
+    /// `Some(None)` for walls.
+    fn offset_position(
+        &self,
+        (row, col): (usize, usize),
+        (row_offset, col_offset): (isize, isize),
+    ) -> Option<Option<(usize, usize)>> {
+        let (rows, cols) = (self.rolls.rows, self.rolls.cols);
+        let offset_row = row.checked_add_signed(row_offset).filter(|row| *row < rows);
+        let offset_col = col.checked_add_signed(col_offset).filter(|col| *col < cols);
+
+        match (offset_row, offset_col, self.rules.edges) {
+            (Some(offset_row), Some(offset_col), _) => Some(Some((offset_row, offset_col))),
+            (_, _, EdgeMode::Empty) => None,
+            (_, _, EdgeMode::Wall) => Some(None),
+            (_, _, EdgeMode::Torus) => {
+                let wrap = |coord: usize, offset: isize, size: usize| {
+                    (coord as i128 + offset as i128).rem_euclid(size as i128) as usize
+                };
+
+                Some(Some((
+                    wrap(row, row_offset, rows),
+                    wrap(col, col_offset, cols),
+                )))
+            }
+        }
+    }
+}
+
+impl Topology for SparseLattice<'_> {
+    fn cell_count(&self) -> usize {
+        self.rolls.len()
+    }
+
+    /// Only neighbours with rolls (or walls) are given, since empty cells don't have an index.
+    fn neighbours(&self, idx: usize) -> impl Iterator<Item = (Option<usize>, u32)> + '_ {
+        let position = self.rolls.positions[idx];
+
+        self.rules
+            .neighbourhood
+            .offsets()
+            .iter()
+            .filter_map(
+                move |&(offset, weight)| match self.offset_position(position, offset)? {
+                    Some(neighbour) => self
+                        .rolls
+                        .indices
+                        .get(&neighbour)
+                        .map(|&neighbour_idx| (Some(neighbour_idx), weight)),
+                    None => Some((None, weight)),
+                },
+            )
+    }
+}